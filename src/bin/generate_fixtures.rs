@@ -35,6 +35,11 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     fs::write(fixture_path("kucoin_response.json"), &res)?;
     eprintln!("  ✓ Generated kucoin_response.json");
 
+    eprintln!("→ Fetching Coinbase data...");
+    let res = fetch_url("https://api.exchange.coinbase.com/products").await?;
+    fs::write(fixture_path("coinbase_response.json"), &res)?;
+    eprintln!("  ✓ Generated coinbase_response.json");
+
     eprintln!("→ Fetching WOO data...");
     let res = fetch_url("https://api.woo.org/v1/public/info").await?;
     fs::write(fixture_path("woo_response.json"), &res)?;
@@ -50,6 +55,38 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     fs::write(fixture_path("stockanalysis_spy.html"), &res)?;
     eprintln!("  ✓ Generated stockanalysis_spy.html");
 
+    eprintln!("→ Fetching StockAnalysis holdings data (SPY)...");
+    let res = fetch_url("https://stockanalysis.com/etf/spy/holdings/__data.json?x-sveltekit-trailing-slash=1&x-sveltekit-invalidated=001").await?;
+    fs::write(fixture_path("stockanalysis_spy_data.json"), &res)?;
+    eprintln!("  ✓ Generated stockanalysis_spy_data.json");
+
+    eprintln!("→ Fetching Nasdaq screener data (NYSE)...");
+    let res = fetch_url(
+        "https://api.nasdaq.com/api/screener/stocks?tableonly=true&download=true&exchange=nyse",
+    )
+    .await?;
+    fs::write(fixture_path("nasdaq_screener_nyse.json"), &res)?;
+    eprintln!("  ✓ Generated nasdaq_screener_nyse.json");
+
+    eprintln!("→ Fetching Nasdaq earnings calendar...");
+    let res = fetch_url("https://api.nasdaq.com/api/calendar/earnings?date=2025-10-30").await?;
+    fs::write(fixture_path("nasdaq_earnings.json"), &res)?;
+    eprintln!("  ✓ Generated nasdaq_earnings.json");
+
+    eprintln!("→ Fetching DefiLlama token emissions...");
+    let res = fetch_url("https://api.llama.fi/emissions").await?;
+    fs::write(fixture_path("defillama_emissions.json"), &res)?;
+    eprintln!("  ✓ Generated defillama_emissions.json");
+
+    eprintln!("→ Fetching Wikipedia index constituents...");
+    let res = fetch_url("https://en.wikipedia.org/wiki/List_of_S%26P_500_companies").await?;
+    fs::write(fixture_path("wikipedia_sp500.html"), &res)?;
+    let res = fetch_url("https://en.wikipedia.org/wiki/Nasdaq-100").await?;
+    fs::write(fixture_path("wikipedia_nasdaq100.html"), &res)?;
+    let res = fetch_url("https://en.wikipedia.org/wiki/Dow_Jones_Industrial_Average").await?;
+    fs::write(fixture_path("wikipedia_dow.html"), &res)?;
+    eprintln!("  ✓ Generated wikipedia_sp500.html, wikipedia_nasdaq100.html, wikipedia_dow.html");
+
     eprintln!("→ Fetching EarningsHub data (this-week) with Playwright...");
     {
         use chrono::{Datelike, Duration, Local};
@@ -83,7 +120,11 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         let url = format!("https://earningshub.com/earnings-calendar/week-of/{week_date}");
         page.goto_builder(&url).timeout(60_000.0).goto().await?;
 
-        page.wait_for_timeout(10_000.0).await;
+        page.wait_for_selector_builder(r#"a[href*="?symbol="]"#)
+            .timeout(60_000.0)
+            .wait_for_selector()
+            .await
+            .map_err(|e| format!("Earnings calendar did not render within 60s: {e}"))?;
 
         let content = page.content().await?;
         fs::write(fixture_path("earningshub_this_week.html"), &content)?;
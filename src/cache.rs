@@ -0,0 +1,220 @@
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use crate::utils;
+
+/// Fixtures answering `--offline` requests without a cached response, by URL
+/// prefix. More specific prefixes come first.
+const FIXTURES: [(&str, &str); 12] = [
+    (
+        "https://api.binance.com/api/v3/exchangeInfo",
+        "binance_response.json",
+    ),
+    (
+        "https://api.kucoin.com/api/v1/market/allTickers",
+        "kucoin_response.json",
+    ),
+    (
+        "https://api.exchange.coinbase.com/products",
+        "coinbase_response.json",
+    ),
+    ("https://api.woo.org/v1/public/info", "woo_response.json"),
+    (
+        "https://api.coingecko.com/api/v3/coins/markets",
+        "coingecko_response.json",
+    ),
+    (
+        "https://stockanalysis.com/etf/spy/holdings/__data.json",
+        "stockanalysis_spy_data.json",
+    ),
+    (
+        "https://stockanalysis.com/etf/spy/holdings",
+        "stockanalysis_spy.html",
+    ),
+    (
+        "https://api.nasdaq.com/api/screener/stocks?tableonly=true&download=true&exchange=nyse",
+        "nasdaq_screener_nyse.json",
+    ),
+    ("https://api.llama.fi/emissions", "defillama_emissions.json"),
+    (
+        "https://en.wikipedia.org/wiki/List_of_S%26P_500_companies",
+        "wikipedia_sp500.html",
+    ),
+    (
+        "https://en.wikipedia.org/wiki/Nasdaq-100",
+        "wikipedia_nasdaq100.html",
+    ),
+    (
+        "https://en.wikipedia.org/wiki/Dow_Jones_Industrial_Average",
+        "wikipedia_dow.html",
+    ),
+];
+
+/// Fixtures for endpoints queried by `?date=`, standing in only for the date
+/// they were captured on so other dates aren't answered with its data.
+const DATED_FIXTURES: [(&str, &str, &str); 1] = [(
+    "https://api.nasdaq.com/api/calendar/earnings",
+    "2025-10-30",
+    "nasdaq_earnings.json",
+)];
+
+/// Responses stored on disk by URL, fresh for `ttl` after they were fetched.
+#[derive(Debug, Clone)]
+pub struct Cache {
+    pub dir: PathBuf,
+    pub ttl: Duration,
+}
+
+/// 64-bit FNV-1a, stable across builds unlike `DefaultHasher`.
+fn fnv1a(data: &str) -> u64 {
+    data.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+fn read(path: &Path) -> Option<String> {
+    let mut body = std::fs::read_to_string(path).ok()?;
+    // `write_file` ends the body with a newline.
+    if body.ends_with('\n') {
+        body.pop();
+    }
+    Some(body)
+}
+
+impl Cache {
+    /// Where `url`'s response is stored, e.g.
+    /// `api.binance.com-3f1c0d8e2a9b7c45.txt`.
+    #[must_use]
+    pub fn path(&self, url: &str) -> PathBuf {
+        let host = reqwest::Url::parse(url)
+            .ok()
+            .and_then(|u| u.host_str().map(str::to_string))
+            .unwrap_or_default();
+        self.dir.join(format!("{host}-{:016x}.txt", fnv1a(url)))
+    }
+
+    /// The stored response for `url` if it is younger than the TTL.
+    #[must_use]
+    pub fn get(&self, url: &str) -> Option<String> {
+        let path = self.path(url);
+        let age = SystemTime::now()
+            .duration_since(path.metadata().ok()?.modified().ok()?)
+            .unwrap_or_default();
+        if age >= self.ttl {
+            return None;
+        }
+        read(&path)
+    }
+
+    /// The stored response for `url`, however old.
+    #[must_use]
+    pub fn get_stale(&self, url: &str) -> Option<String> {
+        read(&self.path(url))
+    }
+
+    /// Stores `body` as the response for `url`.
+    ///
+    /// # Errors
+    ///
+    /// When the cache directory or file can't be written.
+    pub fn put(&self, url: &str, body: &str) -> io::Result<()> {
+        utils::write_file(&[body.to_string()], &self.path(url))
+    }
+}
+
+/// The fixture in `dir` standing in for `url`, if there is one.
+#[must_use]
+pub fn fixture(dir: &Path, url: &str) -> Option<String> {
+    let dated = DATED_FIXTURES
+        .iter()
+        .find(|(prefix, _, _)| url.starts_with(prefix));
+    let file = if let Some((_, date, file)) = dated {
+        let parsed = reqwest::Url::parse(url).ok()?;
+        let (_, queried) = parsed.query_pairs().find(|(key, _)| key == "date")?;
+        if queried != *date {
+            return None;
+        }
+        file
+    } else {
+        let (_, file) = FIXTURES
+            .iter()
+            .find(|(prefix, _)| url.starts_with(prefix))?;
+        file
+    };
+    std::fs::read_to_string(dir.join(file)).ok()
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    fn cache(name: &str, ttl: Duration) -> Cache {
+        Cache {
+            dir: std::env::temp_dir().join(format!("watchlist-{}-{name}", std::process::id())),
+            ttl,
+        }
+    }
+
+    #[test]
+    fn test_round_trip_within_ttl() {
+        let cache = cache("cache-fresh", Duration::from_secs(60));
+        let url = "https://api.binance.com/api/v3/exchangeInfo?permissions=SPOT";
+
+        cache.put(url, "{\"symbols\":[]}").expect("Failed to cache");
+
+        assert_eq!(cache.get(url).as_deref(), Some("{\"symbols\":[]}"));
+        assert_eq!(cache.get("https://api.binance.com/other"), None);
+        let _ = std::fs::remove_dir_all(&cache.dir);
+    }
+
+    #[test]
+    fn test_expired_entries_are_stale() {
+        let cache = cache("cache-expired", Duration::ZERO);
+        let url = "https://api.woo.org/v1/public/info";
+
+        cache.put(url, "body").expect("Failed to cache");
+
+        assert_eq!(cache.get(url), None);
+        assert_eq!(cache.get_stale(url).as_deref(), Some("body"));
+        let _ = std::fs::remove_dir_all(&cache.dir);
+    }
+
+    #[test]
+    fn test_path_is_stable_per_url() {
+        let cache = cache("cache-path", Duration::ZERO);
+        let a = cache.path("https://api.nasdaq.com/api/calendar/earnings?date=2025-10-30");
+        let b = cache.path("https://api.nasdaq.com/api/calendar/earnings?date=2025-10-31");
+
+        assert_ne!(a, b);
+        assert_eq!(
+            a,
+            cache.path("https://api.nasdaq.com/api/calendar/earnings?date=2025-10-30")
+        );
+        assert!(a
+            .file_name()
+            .and_then(|n| n.to_str())
+            .is_some_and(|n| n.starts_with("api.nasdaq.com-")));
+    }
+
+    #[test]
+    fn test_fixture_by_url_prefix() {
+        let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests")
+            .join("fixtures");
+
+        assert!(fixture(&dir, "https://api.woo.org/v1/public/info").is_some());
+        assert!(fixture(
+            &dir,
+            "https://api.nasdaq.com/api/calendar/earnings?date=2025-10-30"
+        )
+        .is_some());
+        assert!(fixture(
+            &dir,
+            "https://api.nasdaq.com/api/calendar/earnings?date=2026-01-05"
+        )
+        .is_none());
+        assert!(fixture(&dir, "https://example.com").is_none());
+    }
+}
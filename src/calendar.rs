@@ -0,0 +1,144 @@
+use chrono::{Datelike, Duration, NaiveDate};
+
+/// An inclusive range of calendar days.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DateRange {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+}
+
+#[must_use]
+pub fn monday_of(date: NaiveDate) -> NaiveDate {
+    let days_since_monday = date.weekday().num_days_from_monday();
+    date.checked_sub_signed(Duration::days(i64::from(days_since_monday)))
+        .unwrap_or(date)
+}
+
+impl DateRange {
+    #[must_use]
+    pub const fn new(from: NaiveDate, to: NaiveDate) -> Self {
+        Self { from, to }
+    }
+
+    /// The Monday-to-Sunday week `offset` weeks away from the week containing
+    /// `today`.
+    #[must_use]
+    pub fn week(today: NaiveDate, offset: i64) -> Self {
+        let this_monday = monday_of(today);
+        let monday = offset
+            .checked_mul(7)
+            .and_then(|days| this_monday.checked_add_signed(Duration::days(days)))
+            .unwrap_or(this_monday);
+        let sunday = monday
+            .checked_add_signed(Duration::days(6))
+            .unwrap_or(monday);
+        Self::new(monday, sunday)
+    }
+
+    #[must_use]
+    pub fn contains(&self, date: NaiveDate) -> bool {
+        self.from <= date && date <= self.to
+    }
+
+    /// Whether the whole week starting on `monday` lies within the range.
+    #[must_use]
+    pub fn covers_week(&self, monday: NaiveDate) -> bool {
+        let sunday = monday
+            .checked_add_signed(Duration::days(6))
+            .unwrap_or(monday);
+        self.contains(monday) && self.contains(sunday)
+    }
+
+    /// Every Monday-to-Friday date in the range.
+    #[must_use]
+    pub fn weekdays(&self) -> Vec<NaiveDate> {
+        self.from
+            .iter_days()
+            .take_while(|date| *date <= self.to)
+            .filter(|date| date.weekday().num_days_from_monday() < 5)
+            .collect()
+    }
+
+    /// The Monday of every week overlapping the range.
+    #[must_use]
+    pub fn mondays(&self) -> Vec<NaiveDate> {
+        let mut mondays = Vec::new();
+        let mut monday = monday_of(self.from);
+        while monday <= self.to {
+            mondays.push(monday);
+            let Some(next) = monday.checked_add_signed(Duration::days(7)) else {
+                break;
+            };
+            monday = next;
+        }
+        mondays
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).expect("Invalid date")
+    }
+
+    #[test]
+    fn test_monday_of() {
+        assert_eq!(monday_of(date(2025, 10, 27)), date(2025, 10, 27));
+        assert_eq!(monday_of(date(2025, 10, 30)), date(2025, 10, 27));
+        assert_eq!(monday_of(date(2025, 11, 2)), date(2025, 10, 27));
+    }
+
+    #[test]
+    fn test_week_offsets() {
+        let thursday = date(2025, 10, 30);
+
+        assert_eq!(
+            DateRange::week(thursday, 0),
+            DateRange::new(date(2025, 10, 27), date(2025, 11, 2))
+        );
+        assert_eq!(DateRange::week(thursday, 1).from, date(2025, 11, 3));
+        assert_eq!(DateRange::week(thursday, 2).from, date(2025, 11, 10));
+        assert_eq!(DateRange::week(thursday, -1).from, date(2025, 10, 20));
+    }
+
+    #[test]
+    fn test_mondays_spanning_weeks() {
+        let range = DateRange::new(date(2025, 10, 30), date(2025, 11, 11));
+
+        assert_eq!(
+            range.mondays(),
+            vec![date(2025, 10, 27), date(2025, 11, 3), date(2025, 11, 10)]
+        );
+    }
+
+    #[test]
+    fn test_mondays_empty_for_inverted_range() {
+        let range = DateRange::new(date(2025, 11, 11), date(2025, 10, 30));
+
+        assert!(range.mondays().is_empty());
+    }
+
+    #[test]
+    fn test_weekdays_skip_weekends() {
+        let range = DateRange::new(date(2025, 10, 31), date(2025, 11, 4));
+
+        assert_eq!(
+            range.weekdays(),
+            vec![date(2025, 10, 31), date(2025, 11, 3), date(2025, 11, 4)]
+        );
+    }
+
+    #[test]
+    fn test_contains_and_covers_week() {
+        let range = DateRange::new(date(2025, 10, 30), date(2025, 11, 9));
+
+        assert!(range.contains(date(2025, 10, 30)));
+        assert!(range.contains(date(2025, 11, 9)));
+        assert!(!range.contains(date(2025, 10, 29)));
+        assert!(range.covers_week(date(2025, 11, 3)));
+        assert!(!range.covers_week(date(2025, 10, 27)));
+    }
+}
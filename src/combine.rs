@@ -0,0 +1,257 @@
+use std::collections::HashSet;
+use std::path::Path;
+use std::str::FromStr;
+
+use color_eyre::eyre::{eyre, Result};
+
+use crate::exchanges::{binance, coinbase, coingecko, kucoin, woo};
+use crate::output::{Format, Output};
+use crate::{symbols, utils};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Op {
+    Union,
+    Intersect,
+    Subtract,
+}
+
+impl FromStr for Op {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "union" | "+" => Ok(Self::Union),
+            "intersect" | "&" => Ok(Self::Intersect),
+            "minus" | "subtract" | "-" => Ok(Self::Subtract),
+            _ => Err(format!("unknown operator: {s}")),
+        }
+    }
+}
+
+/// Splits `a minus b union c` into its first operand and the operations
+/// applied to it, left to right.
+///
+/// # Errors
+///
+/// When the expression is empty, an operator is unknown or an operator has
+/// no operand after it.
+pub fn parse_expression(tokens: &[String]) -> Result<(String, Vec<(Op, String)>), String> {
+    let Some((first, rest)) = tokens.split_first() else {
+        return Err("empty expression".to_string());
+    };
+
+    let mut steps = Vec::new();
+    let mut rest = rest.iter();
+    while let Some(op) = rest.next() {
+        let op: Op = op.parse()?;
+        let operand = rest
+            .next()
+            .ok_or_else(|| format!("missing operand after {op:?}"))?;
+        steps.push((op, operand.clone()));
+    }
+    Ok((first.clone(), steps))
+}
+
+fn key(ticker: &str, by_base: bool) -> String {
+    if by_base {
+        symbols::base_asset(ticker)
+    } else {
+        ticker.to_string()
+    }
+}
+
+/// Applies `op` to two watchlists, ignoring section headers.
+///
+/// Symbols keep the order and spelling of `left`, with `right`'s new symbols
+/// appended on a union. With `by_base`, symbols from different venues match
+/// when they share a base asset, e.g. `KUCOIN:BTCUSDT` and `BINANCE:BTCUSDT`.
+#[must_use]
+pub fn apply(left: &[String], op: Op, right: &[String], by_base: bool) -> Vec<String> {
+    let symbols = |list: &'_ [String]| -> Vec<String> {
+        list.iter()
+            .filter(|line| !line.starts_with("###"))
+            .cloned()
+            .collect()
+    };
+    let left = symbols(left);
+    let right = symbols(right);
+    let right_keys: HashSet<String> = right.iter().map(|s| key(s, by_base)).collect();
+
+    let mut seen = HashSet::new();
+    let mut result: Vec<String> = match op {
+        Op::Union => left.into_iter().chain(right).collect(),
+        Op::Intersect => left
+            .into_iter()
+            .filter(|s| right_keys.contains(&key(s, by_base)))
+            .collect(),
+        Op::Subtract => left
+            .into_iter()
+            .filter(|s| !right_keys.contains(&key(s, by_base)))
+            .collect(),
+    };
+    result.retain(|s| seen.insert(key(s, by_base)));
+    result
+}
+
+/// The current symbols of a live source: binance, kucoin, coingecko,
+/// coinbase, woo-spot or woo-perp.
+pub async fn source(name: &str) -> Option<Vec<String>> {
+    let tickers = match name.to_lowercase().as_str() {
+        "binance" => binance::get_spot().await,
+        "kucoin" => kucoin::get_spot().await,
+        "coingecko" => coingecko::get_top_100().await,
+        "coinbase" => coinbase::get_spot().await,
+        "woo-spot" => woo::get_spot().await,
+        "woo-perp" => woo::get_perp().await,
+        _ => return None,
+    };
+    Some(tickers)
+}
+
+/// A watchlist file, a file in the output directory or a live source.
+///
+/// # Errors
+///
+/// When `name` is neither a readable file nor a known source, or the source
+/// returned no symbols, which is how sources report a failed fetch.
+pub async fn operand(name: &str, output: &Output) -> Result<Vec<String>> {
+    let path = Path::new(name);
+    let saved = output.path(name, Format::Tv.extension());
+    if path.is_file() {
+        return Ok(utils::read_file(path)?);
+    }
+    if saved.is_file() {
+        return Ok(utils::read_file(&saved)?);
+    }
+    let tickers = source(name)
+        .await
+        .ok_or_else(|| eyre!("{name} is neither a watchlist file nor a source"))?;
+    if tickers.is_empty() {
+        return Err(eyre!("source {name} returned no symbols"));
+    }
+    Ok(tickers)
+}
+
+/// Resolves the operands of `expression` and applies its operators left to
+/// right.
+///
+/// # Errors
+///
+/// When the expression is invalid or one of its operands is, see
+/// [`operand`].
+pub async fn evaluate(
+    expression: &[String],
+    by_base: bool,
+    output: &Output,
+) -> Result<Vec<String>> {
+    let (first, steps) = parse_expression(expression).map_err(|e| eyre!(e))?;
+
+    let mut tickers = operand(&first, output).await?;
+    for (op, name) in steps {
+        let right = operand(&name, output).await?;
+        tickers = apply(&tickers, op, &right, by_base);
+    }
+    Ok(tickers)
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    fn list(symbols: &[&str]) -> Vec<String> {
+        symbols.iter().map(|s| (*s).to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_expression() {
+        let (first, steps) = parse_expression(&list(&["kucoin", "minus", "binance", "&", "x"]))
+            .expect("Failed to parse expression");
+
+        assert_eq!(first, "kucoin");
+        assert_eq!(
+            steps,
+            vec![
+                (Op::Subtract, "binance".to_string()),
+                (Op::Intersect, "x".to_string()),
+            ]
+        );
+        assert!(parse_expression(&list(&["a", "minus"])).is_err());
+        assert!(parse_expression(&list(&["a", "xor", "b"])).is_err());
+    }
+
+    #[test]
+    fn test_apply_exact_symbols() {
+        let left = list(&["###Top", "BINANCE:BTCUSDT", "BINANCE:ETHUSDT"]);
+        let right = list(&["BINANCE:ETHUSDT", "BINANCE:SOLUSDT"]);
+
+        assert_eq!(
+            apply(&left, Op::Union, &right, false),
+            list(&["BINANCE:BTCUSDT", "BINANCE:ETHUSDT", "BINANCE:SOLUSDT"])
+        );
+        assert_eq!(
+            apply(&left, Op::Intersect, &right, false),
+            list(&["BINANCE:ETHUSDT"])
+        );
+        assert_eq!(
+            apply(&left, Op::Subtract, &right, false),
+            list(&["BINANCE:BTCUSDT"])
+        );
+    }
+
+    #[test]
+    fn test_apply_by_base_asset() {
+        let kucoin = list(&["KUCOIN:BTCUSDT", "KUCOIN:KCSUSDT"]);
+        let binance = list(&["BINANCE:BTCUSDT", "BINANCE:BNBUSDT"]);
+
+        assert_eq!(
+            apply(&kucoin, Op::Subtract, &binance, false),
+            list(&["KUCOIN:BTCUSDT", "KUCOIN:KCSUSDT"])
+        );
+        assert_eq!(
+            apply(&kucoin, Op::Subtract, &binance, true),
+            list(&["KUCOIN:KCSUSDT"])
+        );
+        assert_eq!(
+            apply(&kucoin, Op::Union, &binance, true),
+            list(&["KUCOIN:BTCUSDT", "KUCOIN:KCSUSDT", "BINANCE:BNBUSDT"])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_evaluate_saved_watchlists() {
+        let dir = std::env::temp_dir().join(format!("watchlist-{}-combine", std::process::id()));
+        let output = Output {
+            formats: vec![Format::Tv],
+            dir: dir.clone(),
+            template: "- {market} - {source}".to_string(),
+            date: chrono::NaiveDate::from_ymd_opt(2025, 10, 30).expect("Invalid date"),
+            history: 0,
+            timestamp: "20251030T120000".to_string(),
+            show_diff: false,
+            new_listings: false,
+        };
+        utils::write_file(
+            &list(&["KUCOIN:BTCUSDT", "KUCOIN:XMRUSDT"]),
+            &output.path("- C - KUCOIN-SPOT", "txt"),
+        )
+        .expect("Failed to write");
+        utils::write_file(
+            &list(&["BINANCE:BTCUSDT"]),
+            &output.path("- C - BINANCE-SPOT", "txt"),
+        )
+        .expect("Failed to write");
+
+        let tickers = evaluate(
+            &list(&["- C - KUCOIN-SPOT", "minus", "- C - BINANCE-SPOT"]),
+            true,
+            &output,
+        )
+        .await
+        .expect("Failed to evaluate");
+
+        assert_eq!(tickers, list(&["KUCOIN:XMRUSDT"]));
+        assert!(operand("no-such-source", &output).await.is_err());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
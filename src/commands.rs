@@ -0,0 +1,684 @@
+use std::collections::{BTreeMap, HashSet};
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use chrono::{Local, NaiveDate, TimeZone};
+use clap::{Args, Parser, Subcommand};
+use color_eyre::eyre::{eyre, Result};
+use tracing::{error, info, info_span, trace, warn, Instrument};
+
+use crate::calendar::DateRange;
+use crate::exchanges::{
+    binance, coinbase, coingecko, defillama, ibkr, indices, kucoin, nasdaq, woo,
+};
+use crate::output::{self, Output};
+use crate::schedule::Schedule;
+use crate::{
+    combine, diff, earnings, events, filters, http, listings, overlap, server, symbols, utils,
+};
+
+/// A command, as parsed from the command line or a job string.
+#[derive(Subcommand)]
+pub enum Commands {
+    #[command(arg_required_else_help = true)]
+    #[group(required = true, multiple = true)]
+    Woo {
+        #[arg(long)]
+        perp: bool,
+        #[arg(long)]
+        spot: bool,
+    },
+    Binance,
+    Coinbase,
+    Kucoin,
+    Coingecko,
+    Ibkr,
+    Components {
+        #[arg(required = true)]
+        etfs: Vec<String>,
+        #[arg(long)]
+        union: bool,
+        #[arg(long)]
+        intersection: bool,
+        #[arg(long)]
+        overlap_report: bool,
+    },
+    #[command(subcommand)]
+    Index(IndexCommands),
+    Earnings(EarningsArgs),
+    #[command(subcommand)]
+    Events(EventsCommands),
+    /// Crypto symbols first listed within the last `days` days on Binance,
+    /// KuCoin, WOO or Coinbase
+    NewListings {
+        #[arg(long, default_value_t = 7)]
+        days: u32,
+    },
+    /// One spot pair per crypto asset across venues
+    AllCrypto {
+        /// Venues in order of preference
+        #[arg(
+            long,
+            value_enum,
+            value_delimiter = ',',
+            default_values_t = [
+                symbols::Venue::Binance,
+                symbols::Venue::Coinbase,
+                symbols::Venue::Kucoin,
+                symbols::Venue::Woo,
+            ]
+        )]
+        priority: Vec<symbols::Venue>,
+        /// Pick the venue with the highest 24h volume instead, where known
+        #[arg(long)]
+        by_volume: bool,
+    },
+    /// Combines watchlists left to right, e.g. `kucoin minus binance` or
+    /// `coingecko intersect woo-perp`. Operands are watchlist files, names of
+    /// files in the output directory or live sources (binance, kucoin,
+    /// coingecko, coinbase, woo-spot, woo-perp); operators are union (+),
+    /// intersect (&) and minus (-).
+    Combine {
+        #[arg(required = true, num_args = 1.., allow_hyphen_values = true)]
+        expression: Vec<String>,
+        /// Match symbols across exchanges by base asset
+        #[arg(long)]
+        by_base: bool,
+        /// Source part of the output file name, defaults to the expression
+        #[arg(long)]
+        name: Option<String>,
+        /// Market part of the output file name
+        #[arg(long, default_value = "C")]
+        market: String,
+    },
+    /// Symbols added and removed between a watchlist and its last history
+    /// copy, or another file
+    Diff {
+        /// Watchlist file name without extension, e.g. "- C - BINANCE-SPOT"
+        name: String,
+        /// Compare against this file instead of the last history copy
+        #[arg(long)]
+        against: Option<PathBuf>,
+    },
+    /// Keeps running and refreshes watchlists on a schedule, e.g.
+    /// `watch --schedule "0 8 * * 1-5" binance "woo --perp --spot"`. Only
+    /// files whose contents changed are rewritten.
+    Watch {
+        /// Cron expression in local time: minute, hour, day of month, month
+        /// and day of week
+        #[arg(long, default_value = "0 8 * * *")]
+        schedule: Schedule,
+        /// Also refresh once at startup
+        #[arg(long)]
+        now: bool,
+        /// Commands to run, each with its arguments in one quoted string
+        #[arg(required = true)]
+        jobs: Vec<String>,
+    },
+    /// Serves the output directory over HTTP: `GET /watchlists`,
+    /// `GET /watchlists/{name}?format=txt|csv|json|plain` and
+    /// `POST /refresh/{name}` for each `--job`. Refreshes bypass the cache.
+    Serve {
+        #[arg(long, default_value = "127.0.0.1:8080")]
+        addr: SocketAddr,
+        /// A job `POST /refresh/{name}` runs, e.g. `--job "woo=woo --perp
+        /// --spot"`. Only these commands can be triggered over HTTP.
+        #[arg(long = "job", value_name = "NAME=COMMAND")]
+        jobs: Vec<String>,
+    },
+}
+
+/// One command run by `watch`.
+#[derive(Parser)]
+#[command(no_binary_name = true)]
+struct Job {
+    #[command(subcommand)]
+    command: Commands,
+}
+
+#[derive(Args)]
+pub struct EarningsArgs {
+    #[command(subcommand)]
+    pub week: Period,
+    /// Where to fetch the earnings calendar from
+    #[arg(long, global = true, value_enum, default_value_t = earnings::Provider::Earningshub)]
+    pub provider: earnings::Provider,
+    /// Seconds to wait for the earningshub calendar to render
+    #[arg(long, global = true, default_value_t = 60)]
+    pub render_timeout: u64,
+    /// Also write one watchlist per report day
+    #[arg(long, global = true)]
+    pub per_day: bool,
+    /// Minimum market cap, e.g. 2B or 500M
+    #[arg(long, global = true, value_parser = filters::parse_market_cap)]
+    pub min_market_cap: Option<f64>,
+    /// Only keep symbols listed on these exchanges (NASDAQ, NYSE, AMEX)
+    #[arg(long, global = true)]
+    pub exchange: Vec<String>,
+    /// Only keep symbols present in these watchlist files (.txt, .csv or .json)
+    #[arg(long, global = true)]
+    pub only_in: Vec<PathBuf>,
+}
+
+#[derive(Subcommand)]
+pub enum IndexCommands {
+    Sp500,
+    Nasdaq100,
+    Dow30,
+    Sectors,
+}
+
+#[derive(Subcommand)]
+pub enum EventsCommands {
+    /// Upcoming token unlocks, as Binance USDT pairs
+    Unlocks {
+        #[command(subcommand)]
+        period: Period,
+    },
+    /// Ex-dividend dates for the holdings of the given ETFs
+    ExDividend {
+        #[arg(long, required = true)]
+        etf: Vec<String>,
+        #[command(subcommand)]
+        period: Period,
+    },
+}
+
+#[derive(Subcommand, Clone, Copy)]
+pub enum Period {
+    ThisWeek,
+    NextWeek,
+    TwoWeeks,
+    /// The week `offset` weeks from the current one
+    Week {
+        #[arg(long, allow_hyphen_values = true, default_value_t = 0)]
+        offset: i64,
+    },
+    /// Every day from `from` to `to`, inclusive (YYYY-MM-DD)
+    Range {
+        #[arg(long)]
+        from: NaiveDate,
+        #[arg(long)]
+        to: NaiveDate,
+    },
+}
+
+impl Commands {
+    /// The period the command covers, for commands that take one.
+    #[must_use]
+    pub const fn period(&self) -> Option<Period> {
+        match self {
+            Self::Earnings(args) => Some(args.week),
+            Self::Events(
+                EventsCommands::Unlocks { period } | EventsCommands::ExDividend { period, .. },
+            ) => Some(*period),
+            _ => None,
+        }
+    }
+
+    /// Checks what clap can't express, i.e. that `--from` isn't after `--to`.
+    ///
+    /// # Errors
+    ///
+    /// When the command's period ends before it starts.
+    pub fn validate(&self) -> Result<(), String> {
+        match self.period() {
+            Some(Period::Range { from, to }) if from > to => {
+                Err(format!("--from {from} is after --to {to}"))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+impl Period {
+    /// The dates covered and the label used in output file names.
+    #[must_use]
+    pub fn resolve(self, today: NaiveDate) -> (DateRange, String) {
+        match self {
+            Self::ThisWeek => (DateRange::week(today, 0), "This Week".to_string()),
+            Self::NextWeek => (DateRange::week(today, 1), "Next Week".to_string()),
+            Self::TwoWeeks => (DateRange::week(today, 2), "Two Weeks".to_string()),
+            Self::Week { offset } => {
+                let range = DateRange::week(today, offset);
+                let label = format!("Week of {}", range.from.format("%Y-%m-%d"));
+                (range, label)
+            }
+            Self::Range { from, to } => {
+                let label = format!("{} to {}", from.format("%Y-%m-%d"), to.format("%Y-%m-%d"));
+                (DateRange::new(from, to), label)
+            }
+        }
+    }
+}
+
+async fn components(
+    etfs: &[String],
+    union: bool,
+    intersection: bool,
+    overlap_report: bool,
+    output: &Output,
+) -> Result<()> {
+    let funds = overlap::get_funds(etfs).await?;
+    for (etf, holdings) in &funds {
+        let tickers: Vec<String> = holdings.iter().map(|h| h.symbol.clone()).collect();
+        utils::handle_file(&tickers, &output.name("E", etf), output);
+    }
+
+    let etfs: Vec<String> = funds.iter().map(|(etf, _)| etf.clone()).collect();
+    let overlaps = overlap::compute(&funds);
+    let combined_name = etfs.join("-");
+
+    if union {
+        let tickers = overlap::union(&overlaps);
+        utils::handle_file(
+            &tickers,
+            &output.name("E", &format!("{combined_name} - Union")),
+            output,
+        );
+    }
+    if intersection {
+        let tickers = overlap::intersection(&overlaps, etfs.len());
+        utils::handle_file(
+            &tickers,
+            &output.name("E", &format!("{combined_name} - Intersection")),
+            output,
+        );
+    }
+    if overlap_report {
+        let rows = overlap::report(&overlaps, &etfs);
+        utils::handle_report(
+            &rows,
+            &output.name("E", &format!("{combined_name} - Overlap")),
+            output,
+        );
+    }
+
+    Ok(())
+}
+
+async fn index(index_cmd: &IndexCommands, output: &Output) -> Result<()> {
+    match index_cmd {
+        IndexCommands::Sp500 => {
+            let constituents = indices::get_constituents(indices::Index::Sp500).await?;
+            utils::handle_file(
+                &indices::tickers(&constituents),
+                &output.name("I", "SP500"),
+                output,
+            );
+        }
+        IndexCommands::Nasdaq100 => {
+            let constituents = indices::get_constituents(indices::Index::Nasdaq100).await?;
+            utils::handle_file(
+                &indices::tickers(&constituents),
+                &output.name("I", "NASDAQ100"),
+                output,
+            );
+        }
+        IndexCommands::Dow30 => {
+            let constituents = indices::get_constituents(indices::Index::Dow30).await?;
+            utils::handle_file(
+                &indices::tickers(&constituents),
+                &output.name("I", "DOW30"),
+                output,
+            );
+        }
+        IndexCommands::Sectors => {
+            let constituents = indices::get_constituents(indices::Index::Sp500).await?;
+            for (sector, spdr, tickers) in indices::by_sector(&constituents) {
+                utils::handle_file(
+                    &tickers,
+                    &output.name("S", &format!("{spdr} {sector}")),
+                    output,
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+async fn earnings(args: &EarningsArgs, output: &Output) -> Result<()> {
+    let (range, label) = args.week.resolve(Local::now().date_naive());
+    let file_name = output.name("Earnings", &label);
+
+    let mut earnings = earnings::get_earnings(
+        args.provider,
+        &range,
+        Duration::from_secs(args.render_timeout),
+    )
+    .await?;
+
+    let listing_filter = filters::ListingFilter {
+        min_market_cap: args.min_market_cap,
+        exchanges: args.exchange.clone(),
+    };
+    if !listing_filter.is_empty() {
+        let listings = nasdaq::get_listings().await?;
+        earnings.retain(|e| listing_filter.matches(&e.symbol, &listings));
+    }
+    if !args.only_in.is_empty() {
+        let universe = filters::read_universe(&args.only_in)?;
+        earnings.retain(|e| universe.contains(&e.symbol));
+    }
+
+    utils::handle_file(&earnings::to_lines(&earnings), &file_name, output);
+
+    if args.per_day {
+        for (date, day) in earnings::by_day(&earnings) {
+            let name = output.name("Earnings", &date.format("%A %Y-%m-%d").to_string());
+            utils::handle_file(&earnings::to_lines(&day), &name, output);
+        }
+    }
+
+    Ok(())
+}
+
+async fn events(events_cmd: &EventsCommands, output: &Output) -> Result<()> {
+    let today = Local::now().date_naive();
+
+    match events_cmd {
+        EventsCommands::Unlocks { period } => {
+            let (range, label) = period.resolve(today);
+            let events = defillama::get_unlocks(&range).await;
+            utils::handle_file(
+                &events::to_lines(&events),
+                &output.name("Unlocks", &label),
+                output,
+            );
+        }
+        EventsCommands::ExDividend { etf, period } => {
+            let (range, label) = period.resolve(today);
+            let holdings: HashSet<String> = overlap::get_funds(etf)
+                .await?
+                .into_iter()
+                .flat_map(|(_, holdings)| holdings.into_iter().map(|h| h.symbol))
+                .collect();
+
+            let mut events = nasdaq::get_ex_dividends(&range).await;
+            events.retain(|e| holdings.contains(&e.symbol));
+
+            let source = format!("{} - {label}", etf.join("-").to_uppercase());
+            let name = output.name("Ex-Dividend", &source);
+            utils::handle_file(&events::to_lines(&events), &name, output);
+        }
+    }
+
+    Ok(())
+}
+
+async fn new_listings(days: u32, output: &Output) -> Result<()> {
+    let today = Local::now().date_naive();
+    let new = listings::refresh(&output.dir.join("snapshots"), today, days).await?;
+    utils::handle_file(
+        &events::to_lines(&new),
+        &output.name("C", "New listings"),
+        output,
+    );
+    Ok(())
+}
+
+/// Runs `jobs` whenever `schedule` matches, and once at startup with `now`,
+/// each with the output settings `new_output` returns for that run.
+///
+/// # Errors
+///
+/// When a job can't be parsed or the schedule never matches. Failing jobs
+/// are logged and don't stop the loop.
+pub async fn watch(
+    schedule: &Schedule,
+    now: bool,
+    jobs: &[String],
+    new_output: impl Fn() -> Output,
+) -> Result<()> {
+    let jobs = jobs
+        .iter()
+        .map(|job| Ok((job, parse_job(job)?)))
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut first = now;
+    loop {
+        if !first {
+            let next = schedule
+                .next_instant(Local::now().naive_local(), |time| {
+                    Local.from_local_datetime(&time)
+                })
+                .ok_or_else(|| eyre!("The schedule never matches"))?;
+            info!(at = %next.format("%Y-%m-%d %H:%M"), "Next refresh");
+            let wait = next.signed_duration_since(Local::now());
+            tokio::time::sleep(wait.to_std().unwrap_or_default()).await;
+        }
+        first = false;
+
+        let output = &new_output();
+        info!(jobs = jobs.len(), "Refreshing");
+        for (job, command) in &jobs {
+            let span = info_span!("job", %job);
+            if let Err(e) = run(command, output).instrument(span).await {
+                error!(%job, error = %e, "Job failed");
+            }
+        }
+    }
+}
+
+/// A command run by `watch` or `serve`, split on whitespace.
+///
+/// # Errors
+///
+/// When `job` isn't a valid command, or is itself `watch` or `serve`.
+pub fn parse_job(job: &str) -> Result<Commands> {
+    let parsed = Job::try_parse_from(job.split_whitespace())
+        .map_err(|e| eyre!("Invalid job {job:?}: {e}"))?;
+    parsed
+        .command
+        .validate()
+        .map_err(|e| eyre!("Invalid job {job:?}: {e}"))?;
+    if matches!(
+        parsed.command,
+        Commands::Watch { .. } | Commands::Serve { .. }
+    ) {
+        return Err(eyre!(
+            "Invalid job {job:?}: watch and serve can't be nested"
+        ));
+    }
+    Ok(parsed.command)
+}
+
+/// A `serve` job given as `NAME=COMMAND`.
+///
+/// # Errors
+///
+/// When the name is missing or contains `/`, or the command is invalid, see
+/// [`parse_job`].
+pub fn parse_named_job(job: &str) -> Result<(String, Commands)> {
+    let (name, command) = job
+        .split_once('=')
+        .ok_or_else(|| eyre!("Invalid job {job:?}: expected NAME=COMMAND"))?;
+    let name = name.trim();
+    if name.is_empty() || name.contains('/') {
+        return Err(eyre!(
+            "Invalid job {job:?}: the name must be non-empty and without /"
+        ));
+    }
+    Ok((name.to_string(), parse_job(command)?))
+}
+
+/// Serves `dir` on `addr` and runs the named `jobs` when a refresh is
+/// requested, bypassing the cache, with the output settings `new_output`
+/// returns for that run.
+///
+/// # Errors
+///
+/// When a job can't be parsed or the server can't be started. Failing
+/// refreshes are reported to the client and don't stop the server.
+pub async fn serve(
+    addr: SocketAddr,
+    dir: &Path,
+    jobs: &[String],
+    new_output: impl Fn() -> Output,
+) -> Result<()> {
+    let jobs = jobs
+        .iter()
+        .map(|job| parse_named_job(job))
+        .collect::<Result<BTreeMap<_, _>>>()?;
+    let names = jobs.keys().cloned().collect();
+    let (addr, mut refreshes) = server::start(addr, dir.to_path_buf(), names).await?;
+    info!(dir = %dir.display(), jobs = jobs.len(), "Serving on http://{addr}");
+
+    while let Some(refresh) = refreshes.recv().await {
+        let span = info_span!("refresh", job = %refresh.name);
+        let outcome = match jobs.get(&refresh.name) {
+            None => server::Outcome::Failed(format!("no job named {:?}", refresh.name)),
+            Some(command) => match http::fresh(run(command, &new_output()))
+                .instrument(span)
+                .await
+            {
+                Ok(()) => server::Outcome::Done,
+                Err(e) => server::Outcome::Failed(e.to_string()),
+            },
+        };
+        if let server::Outcome::Failed(e) = &outcome {
+            warn!(job = %refresh.name, error = %e, "Refresh failed");
+        }
+        let _ = refresh.done.send(outcome);
+    }
+    Ok(())
+}
+
+/// Runs one command, writing its watchlists as `output` says.
+///
+/// # Errors
+///
+/// When the command's source fails in a way it doesn't recover from, or the
+/// command is `watch` or `serve`, which only run from the command line.
+pub async fn run(command: &Commands, output: &Output) -> Result<()> {
+    match command {
+        Commands::Woo { perp, spot } => {
+            if let Some((source, entries)) = woo::get_entries(*perp, *spot).await {
+                trace!(?entries);
+                utils::handle_entries(&entries, &output.name("C", source), output);
+            }
+        }
+        Commands::Binance => {
+            let entries = binance::get_spot_entries().await;
+            let name = output.name("C", "BINANCE-SPOT");
+            trace!(?entries);
+            utils::handle_entries(&entries, &name, output);
+        }
+        Commands::Coinbase => {
+            let tickers = coinbase::get_spot().await;
+            let name = output.name("C", "COINBASE-SPOT");
+            trace!(?tickers);
+            utils::handle_file(&tickers, &name, output);
+        }
+        Commands::Kucoin => {
+            let entries = kucoin::get_spot_entries().await;
+            let name = output.name("C", "KUCOIN-SPOT");
+            trace!(?entries);
+            utils::handle_entries(&entries, &name, output);
+        }
+        Commands::Coingecko => {
+            let tickers = coingecko::get_top_100().await;
+            let name = output.name("C", "COINGECKO-TOP100");
+            trace!(?tickers);
+            utils::handle_file(&tickers, &name, output);
+        }
+        Commands::Ibkr => {
+            let tickers = ibkr::get_tickers().await;
+            let entries = output::sectioned(vec![
+                ("Stocks", output::Entry::from_lines(&tickers.stocks)),
+                ("Options", output::Entry::from_lines(&tickers.options)),
+            ]);
+            trace!(?entries);
+            utils::handle_entries(&entries, &output.name("Positions", "IBKR"), output);
+        }
+        Commands::Components {
+            etfs,
+            union,
+            intersection,
+            overlap_report,
+        } => {
+            components(etfs, *union, *intersection, *overlap_report, output).await?;
+        }
+        Commands::Index(index_cmd) => {
+            index(index_cmd, output).await?;
+        }
+        Commands::Earnings(args) => {
+            earnings(args, output).await?;
+        }
+        Commands::Events(events_cmd) => {
+            events(events_cmd, output).await?;
+        }
+        Commands::NewListings { days } => {
+            new_listings(*days, output).await?;
+        }
+        Commands::AllCrypto {
+            priority,
+            by_volume,
+        } => {
+            let entries = symbols::all_crypto(priority, *by_volume).await;
+            utils::handle_entries(&entries, &output.name("C", "ALL"), output);
+        }
+        Commands::Combine {
+            expression,
+            by_base,
+            name,
+            market,
+        } => {
+            let source = name.clone().unwrap_or_else(|| expression.join(" "));
+            let tickers = combine::evaluate(expression, *by_base, output).await?;
+            utils::handle_file(&tickers, &output.name(market, &source), output);
+        }
+        Commands::Diff { name, against } => {
+            diff::with_previous(name, against.as_deref(), output)?.log(name);
+        }
+        Commands::Watch { .. } | Commands::Serve { .. } => {
+            return Err(eyre!("watch and serve can't run as a job"));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_job() {
+        assert!(matches!(
+            parse_job("woo --perp --spot").expect("Failed to parse job"),
+            Commands::Woo {
+                perp: true,
+                spot: true
+            }
+        ));
+        assert!(parse_job("binance --nope").is_err());
+        assert!(parse_job("watch binance").is_err());
+        assert!(parse_job("earnings range --from 2025-11-02 --to 2025-11-01").is_err());
+    }
+
+    #[test]
+    fn test_parse_named_job() {
+        let (name, command) = parse_named_job("crypto = binance").expect("Failed to parse job");
+
+        assert_eq!(name, "crypto");
+        assert!(matches!(command, Commands::Binance));
+        assert!(parse_named_job("binance").is_err());
+        assert!(parse_named_job("=binance").is_err());
+        assert!(parse_named_job("a/b=binance").is_err());
+    }
+
+    #[test]
+    fn test_period_resolve() {
+        let today = NaiveDate::from_ymd_opt(2025, 10, 30).expect("Invalid date");
+
+        let (range, label) = Period::Week { offset: 1 }.resolve(today);
+        assert_eq!(label, "Week of 2025-11-03");
+        assert_eq!(
+            range.from,
+            NaiveDate::from_ymd_opt(2025, 11, 3).expect("Invalid date")
+        );
+    }
+}
@@ -0,0 +1,117 @@
+use std::collections::HashSet;
+use std::io;
+use std::path::Path;
+use tracing::info;
+
+use crate::output::{self, Format, Output};
+use crate::utils;
+
+/// Symbols added to and removed from a watchlist between two versions.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Diff {
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+}
+
+fn symbols(lines: &[String]) -> impl Iterator<Item = &String> {
+    lines.iter().filter(|line| !line.starts_with("###"))
+}
+
+/// Compares two versions of a watchlist, ignoring section headers. Both lists
+/// keep the order of the version they come from.
+#[must_use]
+pub fn compare(previous: &[String], current: &[String]) -> Diff {
+    let before: HashSet<&String> = symbols(previous).collect();
+    let after: HashSet<&String> = symbols(current).collect();
+
+    Diff {
+        added: symbols(current)
+            .filter(|s| !before.contains(s))
+            .cloned()
+            .collect(),
+        removed: symbols(previous)
+            .filter(|s| !after.contains(s))
+            .cloned()
+            .collect(),
+    }
+}
+
+/// Compares the `name` watchlist with `against`, or with its last history
+/// copy, both read in the first format `output` writes.
+///
+/// # Errors
+///
+/// When either version can't be read, or there is no history copy and no
+/// `against` file.
+pub fn with_previous(name: &str, against: Option<&Path>, output: &Output) -> io::Result<Diff> {
+    let format = output.formats.first().copied().unwrap_or(Format::Tv);
+    let extension = format.extension();
+    let current = utils::read_file(&output.path(name, extension))?;
+    let previous_path = match against {
+        Some(path) => path.to_path_buf(),
+        None => utils::archived(&output.history_dir(name), extension)?
+            .pop()
+            .ok_or_else(|| {
+                io::Error::new(io::ErrorKind::NotFound, format!("No history for {name}"))
+            })?,
+    };
+    let previous = utils::read_file(&previous_path)?;
+
+    Ok(compare(
+        &output::keys(&previous, format),
+        &output::keys(&current, format),
+    ))
+}
+
+impl Diff {
+    /// Logs a summary of the changes to `name`, then each changed symbol.
+    pub fn log(&self, name: &str) {
+        info!(
+            watchlist = name,
+            added = self.added.len(),
+            removed = self.removed.len(),
+            "Diff"
+        );
+        for symbol in &self.added {
+            info!(watchlist = name, symbol = %symbol, change = "added", "Changed");
+        }
+        for symbol in &self.removed {
+            info!(watchlist = name, symbol = %symbol, change = "removed", "Changed");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(symbols: &[&str]) -> Vec<String> {
+        symbols.iter().map(|s| (*s).to_string()).collect()
+    }
+
+    #[test]
+    fn test_compare_ignores_sections_and_order() {
+        let previous = lines(&["###Majors", "BINANCE:BTCUSDT", "BINANCE:LUNAUSDT"]);
+        let current = lines(&[
+            "BINANCE:NEWUSDT",
+            "###Moved",
+            "BINANCE:BTCUSDT",
+            "BINANCE:ANOTHERUSDT",
+        ]);
+
+        assert_eq!(
+            compare(&previous, &current),
+            Diff {
+                added: lines(&["BINANCE:NEWUSDT", "BINANCE:ANOTHERUSDT"]),
+                removed: lines(&["BINANCE:LUNAUSDT"]),
+            }
+        );
+    }
+
+    #[test]
+    fn test_compare_unchanged() {
+        let list = lines(&["KUCOIN:BTCUSDT", "KUCOIN:ETHUSDT"]);
+
+        assert_eq!(compare(&list, &list), Diff::default());
+    }
+}
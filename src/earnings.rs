@@ -0,0 +1,197 @@
+use std::time::Duration;
+
+use chrono::NaiveDate;
+use color_eyre::eyre::Result;
+
+use crate::calendar::DateRange;
+use crate::exchanges::{earningshub, nasdaq};
+use crate::utils;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Session {
+    BeforeOpen,
+    AfterClose,
+    Unknown,
+}
+
+impl Session {
+    #[must_use]
+    pub const fn code(self) -> Option<&'static str> {
+        match self {
+            Self::BeforeOpen => Some("BMO"),
+            Self::AfterClose => Some("AMC"),
+            Self::Unknown => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Earning {
+    pub symbol: String,
+    pub date: Option<NaiveDate>,
+    pub session: Session,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Provider {
+    /// earningshub.com, rendered in headless Chromium
+    Earningshub,
+    /// Nasdaq's earnings calendar API, plain HTTP
+    Nasdaq,
+}
+
+/// `render_timeout` bounds how long the browser-based provider waits for the
+/// calendar page to render.
+///
+/// # Errors
+///
+/// When the earningshub calendar can't be fetched. Nasdaq skips the days it
+/// fails to fetch.
+pub async fn get_earnings(
+    provider: Provider,
+    range: &DateRange,
+    render_timeout: Duration,
+) -> Result<Vec<Earning>> {
+    match provider {
+        Provider::Earningshub => earningshub::get_earnings(range, render_timeout).await,
+        Provider::Nasdaq => Ok(nasdaq::get_earnings(range).await),
+    }
+}
+
+fn section_name(date: Option<NaiveDate>, session: Session) -> String {
+    let day = date.map_or_else(
+        || "Unscheduled".to_string(),
+        |d| d.format("%A %Y-%m-%d").to_string(),
+    );
+    match session.code() {
+        Some(code) => format!("{day} {code}"),
+        None => day,
+    }
+}
+
+/// Symbols ordered by date and session, with a TradingView section header
+/// before each day/session group.
+#[must_use]
+pub fn to_lines(earnings: &[Earning]) -> Vec<String> {
+    let mut sorted: Vec<&Earning> = earnings.iter().collect();
+    sorted.sort_by_key(|e| (e.date.is_none(), e.date, e.session));
+
+    let mut lines = Vec::new();
+    let mut current = None;
+    for earning in sorted {
+        let key = (earning.date, earning.session);
+        if current != Some(key) {
+            lines.push(utils::section_header(&section_name(
+                earning.date,
+                earning.session,
+            )));
+            current = Some(key);
+        }
+        lines.push(earning.symbol.clone());
+    }
+    lines
+}
+
+/// Groups earnings by report date, skipping symbols without a known date.
+#[must_use]
+pub fn by_day(earnings: &[Earning]) -> Vec<(NaiveDate, Vec<Earning>)> {
+    let mut days: Vec<(NaiveDate, Vec<Earning>)> = Vec::new();
+    for earning in earnings {
+        let Some(date) = earning.date else {
+            continue;
+        };
+        match days.iter_mut().find(|(d, _)| *d == date) {
+            Some((_, group)) => group.push(earning.clone()),
+            None => days.push((date, vec![earning.clone()])),
+        }
+    }
+    days.sort_by_key(|(date, _)| *date);
+    days
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn earning(symbol: &str, day: Option<u32>, session: Session) -> Earning {
+        Earning {
+            symbol: symbol.to_string(),
+            date: day.and_then(|d| NaiveDate::from_ymd_opt(2025, 10, d)),
+            session,
+        }
+    }
+
+    fn week() -> Vec<Earning> {
+        vec![
+            earning("WM", Some(27), Session::AfterClose),
+            earning("AAPL", Some(30), Session::AfterClose),
+            earning("LLY", Some(30), Session::BeforeOpen),
+            earning("SOFI", Some(28), Session::BeforeOpen),
+            earning("XYZ", None, Session::Unknown),
+        ]
+    }
+
+    #[test]
+    fn test_to_lines_emits_section_headers() {
+        let lines = to_lines(&week());
+
+        assert_eq!(
+            lines,
+            vec![
+                "###Monday 2025-10-27 AMC",
+                "WM",
+                "###Tuesday 2025-10-28 BMO",
+                "SOFI",
+                "###Thursday 2025-10-30 BMO",
+                "LLY",
+                "###Thursday 2025-10-30 AMC",
+                "AAPL",
+                "###Unscheduled",
+                "XYZ",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_to_lines_keeps_weeks_apart() {
+        let mut earnings = week();
+        earnings.push(Earning {
+            symbol: "NVDA".to_string(),
+            date: NaiveDate::from_ymd_opt(2025, 11, 3),
+            session: Session::AfterClose,
+        });
+
+        let headers: Vec<String> = to_lines(&earnings)
+            .into_iter()
+            .filter(|line| line.starts_with("###Monday"))
+            .collect();
+
+        assert_eq!(
+            headers,
+            vec!["###Monday 2025-10-27 AMC", "###Monday 2025-11-03 AMC"]
+        );
+    }
+
+    #[test]
+    fn test_by_day_groups_dated_earnings() {
+        let days = by_day(&week());
+        let summary: Vec<(String, Vec<&str>)> = days
+            .iter()
+            .map(|(date, group)| {
+                (
+                    date.format("%a").to_string(),
+                    group.iter().map(|e| e.symbol.as_str()).collect(),
+                )
+            })
+            .collect();
+
+        assert_eq!(
+            summary,
+            vec![
+                ("Mon".to_string(), vec!["WM"]),
+                ("Tue".to_string(), vec!["SOFI"]),
+                ("Thu".to_string(), vec!["AAPL", "LLY"]),
+            ]
+        );
+    }
+}
@@ -0,0 +1,65 @@
+use chrono::NaiveDate;
+
+use crate::utils;
+
+/// A symbol with something scheduled on `date`, such as a token unlock or an
+/// ex-dividend date.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Event {
+    pub date: NaiveDate,
+    pub symbol: String,
+}
+
+/// Symbols ordered by date, with a TradingView section header before each
+/// day. Dates are spelled out since a range can span several weeks.
+#[must_use]
+pub fn to_lines(events: &[Event]) -> Vec<String> {
+    let mut sorted: Vec<&Event> = events.iter().collect();
+    sorted.sort();
+
+    let mut lines = Vec::new();
+    let mut current = None;
+    for event in sorted {
+        if current != Some(event.date) {
+            lines.push(utils::section_header(
+                &event.date.format("%A %Y-%m-%d").to_string(),
+            ));
+            current = Some(event.date);
+        }
+        lines.push(event.symbol.clone());
+    }
+    lines
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    fn event(symbol: &str, month: u32, day: u32) -> Event {
+        Event {
+            date: NaiveDate::from_ymd_opt(2025, month, day).expect("Invalid date"),
+            symbol: symbol.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_to_lines_groups_by_date() {
+        let events = vec![
+            event("BINANCE:SUIUSDT", 11, 1),
+            event("BINANCE:ARBUSDT", 10, 16),
+            event("BINANCE:APTUSDT", 11, 1),
+        ];
+
+        assert_eq!(
+            to_lines(&events),
+            vec![
+                "###Thursday 2025-10-16",
+                "BINANCE:ARBUSDT",
+                "###Saturday 2025-11-01",
+                "BINANCE:APTUSDT",
+                "BINANCE:SUIUSDT",
+            ]
+        );
+    }
+}
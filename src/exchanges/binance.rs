@@ -1,5 +1,10 @@
-use reqwest::header;
+use std::collections::HashMap;
+
 use serde_derive::{Deserialize, Serialize};
+use tracing::{error, instrument, warn};
+
+use crate::http;
+use crate::output::Entry;
 
 #[derive(Deserialize, Serialize, Debug)]
 pub struct Response {
@@ -16,30 +21,46 @@ pub struct Symbol {
     pub base_asset: String,
 }
 
-const EXCHANGE_NAME: &str = "BINANCE";
-const API_URL: &str = "https://api.binance.com/api/v3/exchangeInfo?permissions=SPOT";
+#[derive(Deserialize, Serialize, Debug)]
+pub struct Ticker24h {
+    pub symbol: String,
+    /// 24h volume in the base asset
+    pub volume: String,
+}
 
-async fn fetch_data(url: &str) -> Result<String, Box<dyn std::error::Error>> {
-    let client = reqwest::Client::new();
-    let res = client
-        .get(url)
-        .header(
-            header::USER_AGENT,
-            "Mozilla/5.0 (Windows NT 10.0; Win64; x64; rv:109.0) Gecko/20100101 Firefox/113.0",
-        )
-        .send()
-        .await?
-        .text()
-        .await?;
-    Ok(res)
+/// Body Binance sends with a failed request, e.g. a 451 from a restricted
+/// region or a 429 when rate limited.
+#[derive(Deserialize, Debug)]
+struct ApiError {
+    code: i64,
+    msg: String,
 }
 
+fn api_error(body: &str) -> Option<String> {
+    let error: ApiError = serde_json::from_str(body).ok()?;
+    Some(format!("Binance error {}: {}", error.code, error.msg))
+}
+
+const EXCHANGE_NAME: &str = "BINANCE";
+const API_URL: &str = "https://api.binance.com/api/v3/exchangeInfo?permissions=SPOT";
+const TICKER_URL: &str = "https://api.binance.com/api/v3/ticker/24hr?type=MINI";
+
 async fn get_data() -> Result<Response, Box<dyn std::error::Error>> {
-    let res = fetch_data(API_URL).await?;
+    let res = http::get(API_URL).await.map_err(|e| e.decode(api_error))?;
     let parsed: Response = serde_json::from_str(&res)?;
     Ok(parsed)
 }
 
+#[instrument(name = "binance_volumes")]
+async fn get_volumes() -> Result<Vec<Ticker24h>, Box<dyn std::error::Error>> {
+    let res = http::get(TICKER_URL)
+        .await
+        .map_err(|e| e.decode(api_error))?;
+    let parsed: Vec<Ticker24h> = serde_json::from_str(&res)?;
+    Ok(parsed)
+}
+
+#[must_use]
 pub fn process_data(response: Response) -> Vec<String> {
     let blacklist = [
         "TUSD", "USDC", "BUSD", "EUR", "GBP", "PAX", "DAI", "AUD", "USDP", "FDUSD", "WBTC",
@@ -57,10 +78,38 @@ pub fn process_data(response: Response) -> Vec<String> {
         .collect()
 }
 
+/// Spot tickers with their 24h base volume where Binance reports one.
+#[must_use]
+pub fn attach_volumes(tickers: &[String], volumes: &[Ticker24h]) -> Vec<Entry> {
+    let volumes: HashMap<&str, &Ticker24h> =
+        volumes.iter().map(|t| (t.symbol.as_str(), t)).collect();
+
+    let mut entries = Entry::from_lines(tickers);
+    for entry in &mut entries {
+        entry.volume = volumes
+            .get(entry.symbol.as_str())
+            .and_then(|t| t.volume.parse().ok());
+    }
+    entries
+}
+
+pub async fn get_spot_entries() -> Vec<Entry> {
+    let tickers = get_spot().await;
+    let volumes = get_volumes().await.unwrap_or_else(|e| {
+        warn!(error = %e, "Failed to get Binance volumes");
+        vec![]
+    });
+    attach_volumes(&tickers, &volumes)
+}
+
+#[instrument(name = "binance")]
 pub async fn get_spot() -> Vec<String> {
     match get_data().await {
         Ok(data) => process_data(data),
-        Err(_) => vec![],
+        Err(e) => {
+            error!(error = %e, "Failed to get data");
+            vec![]
+        }
     }
 }
 
@@ -215,4 +264,38 @@ mod tests {
             "Binance should have ETH"
         );
     }
+
+    #[test]
+    fn test_attach_volumes() {
+        let tickers = vec!["BINANCE:BTCUSDT".to_string(), "BINANCE:ETHUSDT".to_string()];
+        let volumes: Vec<Ticker24h> = serde_json::from_str(
+            r#"[
+                {"symbol":"BTCUSDT","openPrice":"67000.00","volume":"15234.5","quoteVolume":"1021000000.0"},
+                {"symbol":"BTCFDUSD","openPrice":"67000.00","volume":"9000.0","quoteVolume":"603000000.0"}
+            ]"#,
+        )
+        .expect("Failed to parse ticker JSON");
+
+        let summary: Vec<(String, Option<f64>)> = attach_volumes(&tickers, &volumes)
+            .into_iter()
+            .map(|e| (e.ticker, e.volume))
+            .collect();
+
+        assert_eq!(
+            summary,
+            vec![
+                ("BINANCE:BTCUSDT".to_string(), Some(15234.5)),
+                ("BINANCE:ETHUSDT".to_string(), None),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_api_error() {
+        assert_eq!(
+            api_error(r#"{"code":0,"msg":"Service unavailable from a restricted location."}"#),
+            Some("Binance error 0: Service unavailable from a restricted location.".to_string())
+        );
+        assert_eq!(api_error(r#"{"symbols":[]}"#), None);
+    }
 }
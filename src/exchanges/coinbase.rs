@@ -1 +1,116 @@
+use serde_derive::{Deserialize, Serialize};
+use tracing::{error, instrument};
 
+use crate::http;
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct Product {
+    pub id: String,
+    pub base_currency: String,
+    pub quote_currency: String,
+    pub status: String,
+    #[serde(default)]
+    pub trading_disabled: bool,
+}
+
+const EXCHANGE_NAME: &str = "COINBASE";
+const API_URL: &str = "https://api.exchange.coinbase.com/products";
+
+async fn get_data() -> Result<Vec<Product>, Box<dyn std::error::Error>> {
+    let res = http::get(API_URL).await?;
+    let parsed: Vec<Product> = serde_json::from_str(&res)?;
+    Ok(parsed)
+}
+
+#[must_use]
+pub fn process_data(products: Vec<Product>) -> Vec<String> {
+    let blacklist = ["USDT", "USDC", "DAI", "PYUSD", "EURC", "WBTC"];
+
+    let mut tickers: Vec<String> = products
+        .iter()
+        .filter(|product| {
+            product.status == "online"
+                && !product.trading_disabled
+                && product.quote_currency == "USD"
+                && !blacklist.contains(&product.base_currency.as_str())
+        })
+        .map(|product| format!("{EXCHANGE_NAME}:{}USD", product.base_currency))
+        .collect();
+    tickers.sort();
+    tickers
+}
+
+#[instrument(name = "coinbase")]
+pub async fn get_spot() -> Vec<String> {
+    match get_data().await {
+        Ok(data) => process_data(data),
+        Err(e) => {
+            error!(error = %e, "Failed to get data");
+            vec![]
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    fn product(base: &str, quote: &str, status: &str) -> Product {
+        Product {
+            id: format!("{base}-{quote}"),
+            base_currency: base.to_string(),
+            quote_currency: quote.to_string(),
+            status: status.to_string(),
+            trading_disabled: false,
+        }
+    }
+
+    #[test]
+    fn test_process_data_filters_usd_pairs() {
+        let products = vec![
+            product("ETH", "USD", "online"),
+            product("BTC", "USD", "online"),
+            product("BTC", "EUR", "online"),
+            product("USDT", "USD", "online"),
+            product("OLD", "USD", "delisted"),
+            Product {
+                trading_disabled: true,
+                ..product("HALT", "USD", "online")
+            },
+        ];
+
+        assert_eq!(
+            process_data(products),
+            vec!["COINBASE:BTCUSD".to_string(), "COINBASE:ETHUSD".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_get_spot_from_fixture() {
+        let fixture_path = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests")
+            .join("fixtures")
+            .join("coinbase_response.json");
+
+        if !fixture_path.exists() {
+            eprintln!("Skipping test: fixture file not found");
+            return;
+        }
+
+        let fixture_data = std::fs::read_to_string(&fixture_path)
+            .expect("Failed to read coinbase fixture file - file may be corrupted");
+        let products: Vec<Product> = serde_json::from_str(&fixture_data)
+            .expect("Failed to parse coinbase fixture JSON - file may be corrupted");
+        let result = process_data(products);
+
+        assert!(!result.is_empty());
+        assert!(result.iter().all(|s| s.starts_with("COINBASE:")));
+        assert!(result.iter().all(|s| s.ends_with("USD")));
+        assert!(
+            result.contains(&"COINBASE:BTCUSD".to_string()),
+            "Coinbase should have BTC"
+        );
+        assert!(!result.contains(&"COINBASE:USDTUSD".to_string()));
+    }
+}
@@ -1,5 +1,7 @@
-use reqwest::header;
 use serde_derive::{Deserialize, Serialize};
+use tracing::{error, instrument};
+
+use crate::http;
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct Coin {
@@ -12,27 +14,13 @@ const EXCHANGE_NAME: &str = "BINANCE";
 const API_URL: &str =
     "https://api.coingecko.com/api/v3/coins/markets?vs_currency=usd&order=market_cap_desc&per_page=100&page=1";
 
-async fn fetch_data(url: &str) -> Result<String, Box<dyn std::error::Error>> {
-    let client = reqwest::Client::new();
-    let res = client
-        .get(url)
-        .header(
-            header::USER_AGENT,
-            "Mozilla/5.0 (Windows NT 10.0; Win64; x64; rv:109.0) Gecko/20100101 Firefox/113.0",
-        )
-        .send()
-        .await?
-        .text()
-        .await?;
-    Ok(res)
-}
-
 async fn get_data() -> Result<Vec<Coin>, Box<dyn std::error::Error>> {
-    let res = fetch_data(API_URL).await?;
+    let res = http::get(API_URL).await?;
     let parsed: Vec<Coin> = serde_json::from_str(&res)?;
     Ok(parsed)
 }
 
+#[must_use]
 pub fn process_data(coins: Vec<Coin>) -> Vec<String> {
     let stablecoin_blacklist = [
         "usdt", "usdc", "busd", "dai", "tusd", "usdp", "usdd", "gusd", "paxg", "eurs", "eurt",
@@ -64,10 +52,14 @@ pub fn process_data(coins: Vec<Coin>) -> Vec<String> {
         .collect()
 }
 
+#[instrument(name = "coingecko")]
 pub async fn get_top_100() -> Vec<String> {
     match get_data().await {
         Ok(data) => process_data(data),
-        Err(_) => vec![],
+        Err(e) => {
+            error!(error = %e, "Failed to get data");
+            vec![]
+        }
     }
 }
 
@@ -0,0 +1,172 @@
+use chrono::{DateTime, NaiveDate};
+use serde_derive::{Deserialize, Serialize};
+use tracing::{error, instrument};
+
+use crate::calendar::DateRange;
+use crate::events::Event;
+use crate::http;
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct Emission {
+    pub name: String,
+    #[serde(rename = "tSymbol")]
+    pub symbol: Option<String>,
+    #[serde(default)]
+    pub events: Vec<UnlockEvent>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct UnlockEvent {
+    pub timestamp: i64,
+    #[serde(rename = "unlockType")]
+    pub unlock_type: Option<String>,
+}
+
+const EXCHANGE_NAME: &str = "BINANCE";
+const API_URL: &str = "https://api.llama.fi/emissions";
+
+async fn get_data() -> Result<Vec<Emission>, Box<dyn std::error::Error>> {
+    let res = http::get(API_URL).await?;
+
+    let parsed: Vec<Emission> = serde_json::from_str(&res)?;
+
+    Ok(parsed)
+}
+
+fn unlock_date(event: &UnlockEvent) -> Option<NaiveDate> {
+    DateTime::from_timestamp(event.timestamp, 0).map(|dt| dt.date_naive())
+}
+
+/// Cliff unlocks within `range`. Linear vesting is skipped since it unlocks a
+/// little every day and would list the token on every date.
+#[must_use]
+pub fn process_data(emissions: Vec<Emission>, range: &DateRange) -> Vec<Event> {
+    let mut events: Vec<Event> = emissions
+        .iter()
+        .filter_map(|emission| {
+            let symbol = emission.symbol.as_deref()?.trim().to_uppercase();
+            (!symbol.is_empty()).then_some((symbol, &emission.events))
+        })
+        .flat_map(|(symbol, unlocks)| {
+            unlocks
+                .iter()
+                .filter(|event| event.unlock_type.as_deref() != Some("linear"))
+                .filter_map(unlock_date)
+                .filter(|date| range.contains(*date))
+                .map(move |date| Event {
+                    symbol: format!("{EXCHANGE_NAME}:{symbol}USDT"),
+                    date,
+                })
+        })
+        .collect();
+
+    events.sort();
+    events.dedup();
+    events
+}
+
+#[instrument(name = "defillama", skip_all, fields(from = %range.from, to = %range.to))]
+pub async fn get_unlocks(range: &DateRange) -> Vec<Event> {
+    match get_data().await {
+        Ok(data) => process_data(data, range),
+        Err(e) => {
+            error!(error = %e, "Failed to get token unlocks");
+            vec![]
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).expect("Invalid date")
+    }
+
+    fn unlock(date: NaiveDate, unlock_type: &str) -> UnlockEvent {
+        UnlockEvent {
+            timestamp: date
+                .and_hms_opt(0, 0, 0)
+                .expect("Invalid time")
+                .and_utc()
+                .timestamp(),
+            unlock_type: Some(unlock_type.to_string()),
+        }
+    }
+
+    #[test]
+    fn test_process_data_keeps_cliff_unlocks_in_range() {
+        let emissions = vec![
+            Emission {
+                name: "Arbitrum".to_string(),
+                symbol: Some("arb".to_string()),
+                events: vec![
+                    unlock(date(2025, 10, 16), "cliff"),
+                    unlock(date(2025, 11, 16), "cliff"),
+                ],
+            },
+            Emission {
+                name: "Sui".to_string(),
+                symbol: Some("SUI".to_string()),
+                events: vec![
+                    unlock(date(2025, 11, 1), "cliff"),
+                    unlock(date(2025, 11, 2), "linear"),
+                ],
+            },
+            Emission {
+                name: "Unknown".to_string(),
+                symbol: None,
+                events: vec![unlock(date(2025, 11, 3), "cliff")],
+            },
+        ];
+        let range = DateRange::new(date(2025, 10, 27), date(2025, 11, 30));
+
+        let result = process_data(emissions, &range);
+
+        assert_eq!(
+            result,
+            vec![
+                Event {
+                    symbol: "BINANCE:SUIUSDT".to_string(),
+                    date: date(2025, 11, 1),
+                },
+                Event {
+                    symbol: "BINANCE:ARBUSDT".to_string(),
+                    date: date(2025, 11, 16),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_get_unlocks_from_fixture() {
+        let fixture_path = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests")
+            .join("fixtures")
+            .join("defillama_emissions.json");
+
+        if !fixture_path.exists() {
+            eprintln!("Skipping test: fixture file not found");
+            return;
+        }
+
+        let fixture_data = std::fs::read_to_string(fixture_path)
+            .expect("Failed to read defillama fixture file - file may be corrupted");
+        let emissions: Vec<Emission> = serde_json::from_str(&fixture_data)
+            .expect("Failed to parse defillama fixture JSON - file may be corrupted");
+        let range = DateRange::new(date(2025, 11, 1), date(2025, 11, 30));
+
+        let result = process_data(emissions, &range);
+
+        assert!(!result.is_empty());
+        assert!(result.iter().all(|e| e.symbol.starts_with("BINANCE:")));
+        assert!(result.iter().all(|e| e.symbol.ends_with("USDT")));
+        assert!(result.iter().all(|e| range.contains(e.date)));
+        assert!(
+            result.iter().any(|e| e.symbol == "BINANCE:ARBUSDT"),
+            "Arbitrum unlocks monthly"
+        );
+    }
+}
@@ -1,18 +1,122 @@
+use std::time::{Duration, Instant};
+
+use chrono::NaiveDate;
+use color_eyre::eyre::{eyre, Result};
 use playwright::api::{playwright::Playwright, Page};
+use regex::Regex;
+use scraper::{ElementRef, Html, Selector};
+use tracing::{instrument, warn};
+
+use crate::calendar::DateRange;
+use crate::earnings::{Earning, Session};
+
+fn parse_session(label: &str) -> Session {
+    match label {
+        "Before Open" => Session::BeforeOpen,
+        "After Close" => Session::AfterClose,
+        _ => Session::Unknown,
+    }
+}
+
+/// A symbol as found on the calendar page, with the day column and session
+/// block it was listed under.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RawEarning {
+    pub symbol: String,
+    pub day: Option<String>,
+    pub session: Option<String>,
+}
+
+const SYMBOL_SELECTOR: &str = r#"a[href*="?symbol="]"#;
+const POLL_INTERVAL_MS: f64 = 500.0;
+
+/// The earnings calendar for the week starting on `monday`.
+///
+/// # Errors
+///
+/// When the browser can't be started or the calendar fails to load, render
+/// or parse.
+pub async fn get_earnings_week(
+    monday: NaiveDate,
+    render_timeout: Duration,
+) -> Result<Vec<Earning>> {
+    get_earnings_week_impl(monday, render_timeout)
+        .await
+        .map_err(|e| eyre!("Failed to fetch earnings for the week of {monday}: {e}"))
+}
+
+/// Fetches every calendar week overlapping `range` and keeps the earnings
+/// reported within it. Symbols without a known day are kept only when their
+/// whole week falls inside the range.
+///
+/// # Errors
+///
+/// When any of the weeks can't be fetched, see [`get_earnings_week`].
+#[instrument(name = "earningshub", skip_all, fields(from = %range.from, to = %range.to))]
+pub async fn get_earnings(range: &DateRange, render_timeout: Duration) -> Result<Vec<Earning>> {
+    let mut earnings: Vec<Earning> = Vec::new();
+    for monday in range.mondays() {
+        let week = get_earnings_week(monday, render_timeout).await?;
+        for earning in trim_to_range(week, range, monday) {
+            if !earnings.iter().any(|e| e.symbol == earning.symbol) {
+                earnings.push(earning);
+            }
+        }
+    }
+    Ok(earnings)
+}
+
+fn trim_to_range(week: Vec<Earning>, range: &DateRange, monday: NaiveDate) -> Vec<Earning> {
+    week.into_iter()
+        .filter(|e| {
+            e.date
+                .map_or_else(|| range.covers_week(monday), |d| range.contains(d))
+        })
+        .collect()
+}
+
+/// Waits until the calendar has rendered its symbol links and their count has
+/// stopped changing between two polls, for at most `render_timeout` in all.
+async fn wait_for_calendar(
+    page: &Page,
+    render_timeout: Duration,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let started = Instant::now();
+    let timeout_ms = render_timeout.as_secs_f64() * 1000.0;
+    page.wait_for_selector_builder(SYMBOL_SELECTOR)
+        .timeout(timeout_ms)
+        .wait_for_selector()
+        .await
+        .map_err(|e| {
+            format!(
+                "Earnings calendar did not render within {}s: {e}",
+                render_timeout.as_secs()
+            )
+        })?;
 
-pub async fn get_earnings_week(week_date: &str) -> Vec<String> {
-    match get_earnings_week_impl(week_date).await {
-        Ok(tickers) => tickers,
-        Err(e) => {
-            eprintln!("Failed to fetch earnings data: {e}");
-            Vec::new()
+    let count_js = format!("() => document.querySelectorAll('{SYMBOL_SELECTOR}').length");
+    let mut previous: usize = 0;
+    loop {
+        let count: usize = page.evaluate(&count_js, ()).await?;
+        if count > 0 && count == previous {
+            return Ok(());
         }
+        if started.elapsed() >= render_timeout {
+            warn!(
+                "Earnings calendar still loading after {}s, using {count} symbols",
+                render_timeout.as_secs()
+            );
+            return Ok(());
+        }
+        previous = count;
+        page.wait_for_timeout(POLL_INTERVAL_MS).await;
     }
 }
 
 async fn get_earnings_week_impl(
-    week_date: &str,
-) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    monday: NaiveDate,
+    render_timeout: Duration,
+) -> Result<Vec<Earning>, Box<dyn std::error::Error>> {
     let playwright = Playwright::initialize().await?;
     playwright.prepare()?;
 
@@ -32,49 +136,119 @@ async fn get_earnings_week_impl(
 
     let page = context.new_page().await?;
 
+    let week_date = monday.format("%Y-%m-%d");
     let url = format!("https://earningshub.com/earnings-calendar/week-of/{week_date}");
     page.goto_builder(&url).timeout(60_000.0).goto().await?;
 
-    page.wait_for_timeout(15_000.0).await;
-
-    let tickers = extract_tickers(&page).await;
+    let rendered = wait_for_calendar(&page, render_timeout)
+        .await
+        .map_err(|e| e.to_string());
+    let content = match rendered {
+        Ok(()) => page.content().await.map_err(|e| e.to_string()),
+        Err(e) => Err(e),
+    };
 
     browser.close().await?;
 
-    Ok(tickers)
+    let raw = extract_tickers(&content?)?;
+    Ok(to_earnings(raw, monday))
 }
 
-async fn extract_tickers(page: &Page) -> Vec<String> {
-    let js_code = r#"
-        () => {
-            const symbols = [];
-            const seen = new Set();
-            document.querySelectorAll('a[href*="?symbol="]').forEach(el => {
-                const href = el.getAttribute('href');
-                const match = href.match(/[?&]symbol=([A-Z0-9.-]+)/);
-                if (match && match[1] && !seen.has(match[1])) {
-                    symbols.push(match[1]);
-                    seen.add(match[1]);
-                }
-            });
-            return symbols;
-        }
-    "#;
+const DAYS: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+const SESSIONS: [&str; 2] = ["Before Open", "After Close"];
+
+/// Walks the rendered calendar in document order, noting the day column and
+/// session block of each symbol link.
+///
+/// The day column starts at a `Mon`..`Sun` label followed by the day number
+/// and the session block at a `Before Open` / `After Close` title. Symbols
+/// are kept once, at their first occurrence.
+///
+/// # Errors
+///
+/// When the calendar selectors or the symbol pattern fail to compile.
+pub fn extract_tickers(html: &str) -> Result<Vec<RawEarning>, Box<dyn std::error::Error>> {
+    let document = Html::parse_document(html);
+    let selector = Selector::parse(&format!("span, div[title], {SYMBOL_SELECTOR}"))
+        .map_err(|e| format!("Failed to parse calendar selector: {e:?}"))?;
+    let symbol_re = Regex::new(r"[?&]symbol=([A-Z0-9.-]+)")?;
 
-    let result = page.evaluate(js_code, ()).await;
+    let mut rows: Vec<RawEarning> = Vec::new();
+    let mut day: Option<String> = None;
+    let mut session: Option<String> = None;
 
-    match result {
-        Ok(value) => serde_json::from_value(value).unwrap_or_else(|_| Vec::new()),
-        Err(e) => {
-            eprintln!("Failed to extract tickers: {e}");
-            Vec::new()
+    for el in document.select(&selector) {
+        match el.value().name() {
+            "span" => {
+                let text = el.text().collect::<String>();
+                let text = text.trim();
+                let next_is_day_number = el
+                    .next_siblings()
+                    .find_map(ElementRef::wrap)
+                    .is_some_and(|next| next.value().name() == "p");
+                if DAYS.contains(&text) && next_is_day_number {
+                    day = Some(text.to_string());
+                    session = None;
+                }
+            }
+            "div" => {
+                if let Some(title) = el.value().attr("title").filter(|t| SESSIONS.contains(t)) {
+                    session = Some(title.to_string());
+                }
+            }
+            _ => {
+                let symbol = el
+                    .value()
+                    .attr("href")
+                    .and_then(|href| symbol_re.captures(href))
+                    .and_then(|caps| caps.get(1))
+                    .map(|m| m.as_str().to_string());
+                if let Some(symbol) = symbol {
+                    if !rows.iter().any(|row| row.symbol == symbol) {
+                        rows.push(RawEarning {
+                            symbol,
+                            day: day.clone(),
+                            session: session.clone(),
+                        });
+                    }
+                }
+            }
         }
     }
+
+    Ok(rows)
+}
+
+fn day_offset(day: &str) -> Option<i64> {
+    DAYS.iter()
+        .position(|d| *d == day)
+        .and_then(|i| i64::try_from(i).ok())
+}
+
+/// Resolves each symbol's day column against the week starting on `monday`.
+#[must_use]
+pub fn to_earnings(raw: Vec<RawEarning>, monday: NaiveDate) -> Vec<Earning> {
+    raw.into_iter()
+        .map(|row| Earning {
+            date: row
+                .day
+                .as_deref()
+                .and_then(day_offset)
+                .and_then(|offset| monday.checked_add_signed(chrono::Duration::days(offset))),
+            session: row
+                .session
+                .as_deref()
+                .map_or(Session::Unknown, parse_session),
+            symbol: row.symbol,
+        })
+        .collect()
 }
 
 #[cfg(test)]
 #[allow(clippy::expect_used)]
 mod tests {
+    use super::*;
+
     #[test]
     fn test_extract_tickers_from_fixture() {
         let fixture_path = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"))
@@ -90,24 +264,32 @@ mod tests {
             return;
         };
 
-        assert!(
-            html.contains("Earnings Hub"),
-            "Fixture should contain Earnings Hub page"
+        let rows = extract_tickers(&html).expect(
+            "Failed to extract earningshub tickers - file may be corrupted or HTML structure changed",
         );
+
         assert!(
-            html.contains("earnings-calendar"),
-            "Fixture should reference earnings calendar"
+            !rows.is_empty(),
+            "Fixture should contain at least one ticker symbol"
         );
-
-        let Ok(re) = regex::Regex::new(r"[?&]symbol=([A-Z0-9.-]+)") else {
-            return;
+        let is_ticker = |symbol: &str| {
+            symbol.starts_with(|c: char| c.is_ascii_uppercase())
+                && symbol
+                    .chars()
+                    .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '.' || c == '-')
         };
-        let ticker_count = re.captures_iter(&html).count();
+        assert!(rows.iter().all(|r| is_ticker(&r.symbol)));
+        assert!(rows
+            .iter()
+            .all(|r| r.day.as_deref().and_then(day_offset).is_some_and(|i| i < 5)));
+        assert!(rows
+            .iter()
+            .all(|r| r.session.as_deref().is_some_and(|s| SESSIONS.contains(&s))));
 
-        assert!(
-            ticker_count > 0,
-            "Fixture should contain at least one ticker symbol in URL parameters"
-        );
+        let monday = NaiveDate::from_ymd_opt(2025, 10, 27).expect("Invalid date");
+        let earnings = to_earnings(rows, monday);
+        assert!(earnings.iter().all(|e| e.date.is_some()));
+        assert!(earnings.iter().all(|e| e.session != Session::Unknown));
     }
 
     #[test]
@@ -119,24 +301,10 @@ mod tests {
             <a href="/earnings-calendar/week-of/2025-11-10?symbol=GOOG">Google</a>
         "#;
 
-        let mut tickers = Vec::new();
-        let mut seen = std::collections::HashSet::new();
-        let re = regex::Regex::new(r"[?&]symbol=([A-Z0-9.-]+)")
-            .expect("Failed to compile regex - pattern is invalid");
-
-        for cap in re.captures_iter(html) {
-            if let Some(symbol) = cap.get(1) {
-                let sym = symbol.as_str();
-                if !seen.contains(sym) {
-                    tickers.push(sym.to_string());
-                    seen.insert(sym.to_string());
-                }
-            }
-        }
+        let rows = extract_tickers(html).expect("Failed to extract tickers");
+        let tickers: Vec<&str> = rows.iter().map(|r| r.symbol.as_str()).collect();
 
-        assert_eq!(tickers.len(), 4);
-        let expected = vec!["TSLA", "AAPL", "MSFT", "GOOG"];
-        assert!(tickers.iter().zip(&expected).all(|(a, b)| a == b));
+        assert_eq!(tickers, vec!["TSLA", "AAPL", "MSFT", "GOOG"]);
     }
 
     #[test]
@@ -148,23 +316,96 @@ mod tests {
             <a href="/earnings-calendar/week-of/2025-11-10?symbol=TSLA">Tesla</a>
         "#;
 
-        let mut tickers = Vec::new();
-        let mut seen = std::collections::HashSet::new();
-        let re = regex::Regex::new(r"[?&]symbol=([A-Z0-9.-]+)")
-            .expect("Failed to compile regex - pattern is invalid");
-
-        for cap in re.captures_iter(html) {
-            if let Some(symbol) = cap.get(1) {
-                let sym = symbol.as_str();
-                if !seen.contains(sym) {
-                    tickers.push(sym.to_string());
-                    seen.insert(sym.to_string());
-                }
-            }
+        let rows = extract_tickers(html).expect("Failed to extract tickers");
+        let tickers: Vec<&str> = rows.iter().map(|r| r.symbol.as_str()).collect();
+
+        assert_eq!(tickers, vec!["AAPL", "MSFT", "TSLA"]);
+    }
+
+    #[test]
+    fn test_extract_tickers_tracks_day_and_session() {
+        let html = r#"
+            <div><span>Mon</span><p>10</p></div>
+            <div title="After Close"><span>After Close</span></div>
+            <a href="/earnings-calendar/week-of/2025-11-10?symbol=WM"><span title="Waste Management">WM</span></a>
+            <div><span>Tue</span><p>11</p></div>
+            <div title="Before Open"><span>Before Open</span></div>
+            <a href="/earnings-calendar/week-of/2025-11-10?symbol=SOFI">SOFI</a>
+            <div title="After Close"><span>After Close</span></div>
+            <a href="/earnings-calendar/week-of/2025-11-10?symbol=V">V</a>
+        "#;
+
+        let rows = extract_tickers(html).expect("Failed to extract tickers");
+
+        assert_eq!(
+            rows,
+            vec![
+                raw("WM", Some("Mon"), Some("After Close")),
+                raw("SOFI", Some("Tue"), Some("Before Open")),
+                raw("V", Some("Tue"), Some("After Close")),
+            ]
+        );
+    }
+
+    fn raw(symbol: &str, day: Option<&str>, session: Option<&str>) -> RawEarning {
+        RawEarning {
+            symbol: symbol.to_string(),
+            day: day.map(str::to_string),
+            session: session.map(str::to_string),
         }
+    }
+
+    fn week() -> Vec<Earning> {
+        let monday = NaiveDate::from_ymd_opt(2025, 10, 27).expect("Invalid date");
+        to_earnings(
+            vec![
+                raw("WM", Some("Mon"), Some("After Close")),
+                raw("AAPL", Some("Thu"), Some("After Close")),
+                raw("LLY", Some("Thu"), Some("Before Open")),
+                raw("SOFI", Some("Tue"), Some("Before Open")),
+                raw("XYZ", None, None),
+            ],
+            monday,
+        )
+    }
+
+    #[test]
+    fn test_to_earnings_resolves_dates_and_sessions() {
+        let earnings = week();
+
+        assert_eq!(
+            earnings.first(),
+            Some(&Earning {
+                symbol: "WM".to_string(),
+                date: NaiveDate::from_ymd_opt(2025, 10, 27),
+                session: Session::AfterClose,
+            })
+        );
+        assert_eq!(
+            earnings.get(2),
+            Some(&Earning {
+                symbol: "LLY".to_string(),
+                date: NaiveDate::from_ymd_opt(2025, 10, 30),
+                session: Session::BeforeOpen,
+            })
+        );
+        assert_eq!(
+            earnings.last().map(|e| (e.date, e.session)),
+            Some((None, Session::Unknown))
+        );
+    }
+
+    #[test]
+    fn test_trim_to_range() {
+        let monday = NaiveDate::from_ymd_opt(2025, 10, 27).expect("Invalid date");
+        let range = DateRange::new(
+            NaiveDate::from_ymd_opt(2025, 10, 28).expect("Invalid date"),
+            NaiveDate::from_ymd_opt(2025, 10, 30).expect("Invalid date"),
+        );
+
+        let trimmed = trim_to_range(week(), &range, monday);
+        let symbols: Vec<&str> = trimmed.iter().map(|e| e.symbol.as_str()).collect();
 
-        assert_eq!(tickers.len(), 3);
-        let expected = vec!["AAPL", "MSFT", "TSLA"];
-        assert!(tickers.iter().zip(&expected).all(|(a, b)| a == b));
+        assert_eq!(symbols, vec!["AAPL", "LLY", "SOFI"]);
     }
 }
@@ -1,13 +1,14 @@
 use ibapi::accounts::PositionUpdate;
 use ibapi::prelude::SecurityType;
 use ibapi::Client;
+use tracing::{debug, error, instrument, warn};
 
 async fn get_client() -> Result<Client, Box<dyn std::error::Error>> {
     let connection_url = "127.0.0.1:7496";
 
-    eprintln!("connecting to {connection_url}");
+    debug!(url = connection_url, "Connecting to TWS");
     let client = Client::connect(connection_url, 100).await?;
-    eprintln!("connected successfully");
+    debug!("Connected");
     Ok(client)
 }
 
@@ -16,11 +17,12 @@ pub struct Tickers {
     pub options: Vec<String>,
 }
 
+#[instrument(name = "ibkr")]
 pub async fn get_tickers() -> Tickers {
     match get_tickers_impl().await {
         Ok(tickers) => tickers,
         Err(e) => {
-            eprintln!("Failed to get IBKR positions: {e}");
+            error!(error = %e, "Failed to get IBKR positions");
             Tickers {
                 stocks: Vec::new(),
                 options: Vec::new(),
@@ -34,7 +36,7 @@ async fn get_tickers_impl() -> Result<Tickers, Box<dyn std::error::Error>> {
     let mut options = Vec::new();
 
     let client = get_client().await?;
-    eprintln!("Getting positions");
+    debug!("Getting positions");
     let mut subscription = client.positions().await?;
 
     while let Some(position_result) = subscription.next().await {
@@ -68,7 +70,7 @@ async fn get_tickers_impl() -> Result<Tickers, Box<dyn std::error::Error>> {
             },
             Ok(PositionUpdate::PositionEnd) => break,
             Err(e) => {
-                eprintln!("Error receiving position: {e}");
+                warn!(error = %e, "Error receiving position");
                 break;
             }
         }
@@ -0,0 +1,329 @@
+use color_eyre::eyre::Result;
+use scraper::{ElementRef, Html, Selector};
+use tracing::instrument;
+
+use crate::http;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Index {
+    Sp500,
+    Nasdaq100,
+    Dow30,
+}
+
+impl Index {
+    const fn url(self) -> &'static str {
+        match self {
+            Self::Sp500 => "https://en.wikipedia.org/wiki/List_of_S%26P_500_companies",
+            Self::Nasdaq100 => "https://en.wikipedia.org/wiki/Nasdaq-100",
+            Self::Dow30 => "https://en.wikipedia.org/wiki/Dow_Jones_Industrial_Average",
+        }
+    }
+
+    /// Exchange used when neither an exchange column nor a quote link
+    /// identifies the listing venue.
+    const fn default_exchange(self) -> &'static str {
+        match self {
+            Self::Nasdaq100 => "NASDAQ",
+            Self::Sp500 | Self::Dow30 => "NYSE",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Constituent {
+    pub symbol: String,
+    pub exchange: String,
+    pub sector: Option<String>,
+}
+
+impl Constituent {
+    #[must_use]
+    pub fn ticker(&self) -> String {
+        format!("{}:{}", self.exchange, self.symbol)
+    }
+}
+
+/// GICS sectors with the Select Sector SPDR fund tracking each one.
+pub const SECTOR_SPDRS: [(&str, &str); 11] = [
+    ("Communication Services", "XLC"),
+    ("Consumer Discretionary", "XLY"),
+    ("Consumer Staples", "XLP"),
+    ("Energy", "XLE"),
+    ("Financials", "XLF"),
+    ("Health Care", "XLV"),
+    ("Industrials", "XLI"),
+    ("Information Technology", "XLK"),
+    ("Materials", "XLB"),
+    ("Real Estate", "XLRE"),
+    ("Utilities", "XLU"),
+];
+
+async fn get_html(url: &str) -> Result<String> {
+    Ok(http::get(url).await?)
+}
+
+fn cell_text(cell: &ElementRef) -> String {
+    cell.text().collect::<String>().trim().to_string()
+}
+
+fn exchange_from_link(href: &str) -> Option<&'static str> {
+    if href.contains("nyse.com") {
+        Some("NYSE")
+    } else if href.contains("nasdaq.com") {
+        Some("NASDAQ")
+    } else if href.contains("cboe.com") {
+        Some("CBOE")
+    } else {
+        None
+    }
+}
+
+fn normalize_exchange(exchange: &str) -> String {
+    match exchange.to_uppercase().as_str() {
+        "NEW YORK STOCK EXCHANGE" => "NYSE".to_string(),
+        "NASDAQ GLOBAL SELECT" | "NASDAQ GS" => "NASDAQ".to_string(),
+        other => other.to_string(),
+    }
+}
+
+/// Constituents listed in the `constituents` table of an index page.
+///
+/// # Errors
+///
+/// When the page has no constituents table.
+pub fn parse_html(html: &str, index: Index) -> Result<Vec<Constituent>> {
+    let document = Html::parse_document(html);
+    let table_selector = Selector::parse("table#constituents")
+        .map_err(|e| color_eyre::eyre::eyre!("Failed to parse table selector: {:?}", e))?;
+    let row_selector = Selector::parse("tr")
+        .map_err(|e| color_eyre::eyre::eyre!("Failed to parse row selector: {:?}", e))?;
+    let cell_selector = Selector::parse("th, td")
+        .map_err(|e| color_eyre::eyre::eyre!("Failed to parse cell selector: {:?}", e))?;
+    let link_selector = Selector::parse("a")
+        .map_err(|e| color_eyre::eyre::eyre!("Failed to parse link selector: {:?}", e))?;
+
+    let table = document
+        .select(&table_selector)
+        .next()
+        .ok_or_else(|| color_eyre::eyre::eyre!("Constituents table not found in HTML"))?;
+
+    let mut rows = table.select(&row_selector);
+    let headers: Vec<String> = rows
+        .next()
+        .ok_or_else(|| color_eyre::eyre::eyre!("Constituents table has no header row"))?
+        .select(&cell_selector)
+        .map(|cell| cell_text(&cell).to_lowercase())
+        .collect();
+
+    let column = |names: &[&str]| {
+        headers
+            .iter()
+            .position(|header| names.iter().any(|name| header.starts_with(name)))
+    };
+    let symbol_column = column(&["symbol", "ticker"])
+        .ok_or_else(|| color_eyre::eyre::eyre!("Symbol column not found in constituents table"))?;
+    let exchange_column = column(&["exchange"]);
+    let sector_column = column(&["gics sector"]);
+
+    let constituents = rows
+        .filter_map(|row| {
+            let cells: Vec<ElementRef> = row.select(&cell_selector).collect();
+            let symbol_cell = cells.get(symbol_column)?;
+            let symbol = cell_text(symbol_cell);
+            if symbol.is_empty() {
+                return None;
+            }
+
+            let exchange = exchange_column
+                .and_then(|i| cells.get(i))
+                .map(cell_text)
+                .filter(|exchange| !exchange.is_empty())
+                .map(|exchange| normalize_exchange(&exchange))
+                .or_else(|| {
+                    symbol_cell
+                        .select(&link_selector)
+                        .next()
+                        .and_then(|link| link.value().attr("href"))
+                        .and_then(exchange_from_link)
+                        .map(str::to_string)
+                })
+                .unwrap_or_else(|| index.default_exchange().to_string());
+
+            let sector = sector_column
+                .and_then(|i| cells.get(i))
+                .map(cell_text)
+                .filter(|sector| !sector.is_empty());
+
+            Some(Constituent {
+                symbol,
+                exchange,
+                sector,
+            })
+        })
+        .collect();
+
+    Ok(constituents)
+}
+
+pub fn tickers(constituents: &[Constituent]) -> Vec<String> {
+    constituents.iter().map(Constituent::ticker).collect()
+}
+
+/// Splits constituents into one list per GICS sector, in `SECTOR_SPDRS` order.
+#[must_use]
+pub fn by_sector(constituents: &[Constituent]) -> Vec<(&'static str, &'static str, Vec<String>)> {
+    SECTOR_SPDRS
+        .iter()
+        .map(|(sector, spdr)| {
+            let tickers = constituents
+                .iter()
+                .filter(|c| c.sector.as_deref() == Some(*sector))
+                .map(Constituent::ticker)
+                .collect();
+            (*sector, *spdr, tickers)
+        })
+        .collect()
+}
+
+/// The current constituents of `index`, from its Wikipedia page.
+///
+/// # Errors
+///
+/// When the page can't be fetched or its constituents table parsed.
+#[instrument(name = "indices")]
+pub async fn get_constituents(index: Index) -> Result<Vec<Constituent>> {
+    let html = get_html(index.url()).await?;
+    parse_html(&html, index)
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    fn load_fixture(filename: &str) -> Option<String> {
+        let fixture_path = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests")
+            .join("fixtures")
+            .join(filename);
+
+        if !fixture_path.exists() {
+            eprintln!("Skipping test: fixture file not found");
+            return None;
+        }
+
+        Some(
+            std::fs::read_to_string(fixture_path)
+                .expect("Failed to read wikipedia fixture file - file may be corrupted"),
+        )
+    }
+
+    #[test]
+    fn test_parse_html_missing_table() {
+        let html = r#"
+            <html>
+                <body>
+                    <table id="changes"><tr><th>Ticker</th></tr></table>
+                </body>
+            </html>
+        "#;
+
+        assert!(parse_html(html, Index::Sp500).is_err());
+    }
+
+    #[test]
+    fn test_parse_html_default_exchange() {
+        let html = r#"
+            <table id="constituents">
+                <tr><th>Ticker</th><th>Company</th></tr>
+                <tr><td>MSFT</td><td>Microsoft</td></tr>
+            </table>
+        "#;
+
+        let result = parse_html(html, Index::Nasdaq100).expect("Failed to parse HTML");
+
+        assert_eq!(tickers(&result), vec!["NASDAQ:MSFT"]);
+    }
+
+    #[test]
+    fn test_sp500_from_fixture() {
+        let Some(html) = load_fixture("wikipedia_sp500.html") else {
+            return;
+        };
+
+        let constituents = parse_html(&html, Index::Sp500).expect(
+            "Failed to parse S&P 500 HTML - file may be corrupted or HTML structure changed",
+        );
+        let result = tickers(&constituents);
+
+        assert!(result.contains(&"NYSE:MMM".to_string()));
+        assert!(result.contains(&"NASDAQ:AAPL".to_string()));
+        assert!(result.contains(&"NYSE:BRK.B".to_string()));
+        assert!(result.contains(&"CBOE:CBOE".to_string()));
+        // Rows of the changes table have no sector and would repeat symbols.
+        assert!(constituents.iter().all(|c| c.sector.is_some()));
+        let unique: HashSet<&String> = result.iter().collect();
+        assert_eq!(unique.len(), result.len());
+    }
+
+    #[test]
+    fn test_by_sector_from_fixture() {
+        let Some(html) = load_fixture("wikipedia_sp500.html") else {
+            return;
+        };
+
+        let constituents = parse_html(&html, Index::Sp500).expect(
+            "Failed to parse S&P 500 HTML - file may be corrupted or HTML structure changed",
+        );
+        let sectors = by_sector(&constituents);
+
+        assert_eq!(sectors.len(), SECTOR_SPDRS.len());
+        let (_, _, technology) = sectors
+            .iter()
+            .find(|(_, spdr, _)| *spdr == "XLK")
+            .expect("Information Technology sector should be present");
+        assert!(technology.contains(&"NASDAQ:AAPL".to_string()));
+        assert!(constituents.iter().all(|c| SECTOR_SPDRS
+            .iter()
+            .any(|(sector, _)| c.sector.as_deref() == Some(*sector))));
+
+        let listed: usize = sectors.iter().map(|(_, _, tickers)| tickers.len()).sum();
+        assert_eq!(listed, constituents.len());
+    }
+
+    #[test]
+    fn test_nasdaq100_from_fixture() {
+        let Some(html) = load_fixture("wikipedia_nasdaq100.html") else {
+            return;
+        };
+
+        let constituents = parse_html(&html, Index::Nasdaq100).expect(
+            "Failed to parse Nasdaq-100 HTML - file may be corrupted or HTML structure changed",
+        );
+        let result = tickers(&constituents);
+
+        assert!(!result.is_empty());
+        assert!(result.iter().all(|t| t.starts_with("NASDAQ:")));
+        assert!(result.contains(&"NASDAQ:NVDA".to_string()));
+    }
+
+    #[test]
+    fn test_dow30_from_fixture() {
+        let Some(html) = load_fixture("wikipedia_dow.html") else {
+            return;
+        };
+
+        let constituents = parse_html(&html, Index::Dow30)
+            .expect("Failed to parse Dow HTML - file may be corrupted or HTML structure changed");
+        let result = tickers(&constituents);
+
+        assert!(!result.is_empty() && result.len() <= 30);
+        assert!(result
+            .iter()
+            .all(|t| t.starts_with("NYSE:") || t.starts_with("NASDAQ:")));
+        assert!(result.contains(&"NYSE:GS".to_string()));
+        assert!(result.contains(&"NASDAQ:AAPL".to_string()));
+    }
+}
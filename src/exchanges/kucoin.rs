@@ -1,5 +1,10 @@
-use reqwest::header;
+use std::collections::HashMap;
+
 use serde_derive::{Deserialize, Serialize};
+use tracing::{error, instrument};
+
+use crate::http;
+use crate::output::Entry;
 
 #[derive(Deserialize, Serialize, Debug)]
 pub struct ResponseTicker {
@@ -35,25 +40,38 @@ pub struct Response {
     pub data: ResponseData,
 }
 
+/// KuCoin wraps every response in a `code`, which is `200000` on success,
+/// and explains failures in `msg`, sometimes with a 200 status.
+#[derive(Deserialize, Debug)]
+struct Envelope {
+    code: String,
+    #[serde(default)]
+    msg: String,
+}
+
+const SUCCESS_CODE: &str = "200000";
+
+fn api_error(body: &str) -> Option<String> {
+    let envelope: Envelope = serde_json::from_str(body).ok()?;
+    (envelope.code != SUCCESS_CODE)
+        .then(|| format!("KuCoin error {}: {}", envelope.code, envelope.msg))
+}
+
+fn parse(body: &str) -> Result<Response, Box<dyn std::error::Error>> {
+    if let Some(error) = api_error(body) {
+        return Err(error.into());
+    }
+    Ok(serde_json::from_str(body)?)
+}
+
 async fn get_data() -> Result<Response, Box<dyn std::error::Error>> {
     let api_url = "https://api.kucoin.com/api/v1/market/allTickers";
-    let client = reqwest::Client::new();
-    let res = client
-        .get(api_url)
-        .header(
-            header::USER_AGENT,
-            "Mozilla/5.0 (Windows NT 10.0; Win64; x64; rv:109.0) Gecko/20100101 Firefox/113.0",
-        )
-        .send()
-        .await?
-        .text()
-        .await?;
-
-    let parsed: Response = serde_json::from_str(&res)?;
-
-    Ok(parsed)
+    let res = http::get(api_url).await.map_err(|e| e.decode(api_error))?;
+
+    parse(&res)
 }
 
+#[must_use]
 pub fn process_data(mut tickers: Vec<ResponseTicker>) -> Vec<String> {
     let Ok(regex) = regex::Regex::new(r"3L|3S|2L|2S|DOWN") else {
         return Vec::new();
@@ -91,11 +109,40 @@ fn get_spot_impl(response: Response) -> Vec<String> {
         .collect()
 }
 
+/// Spot tickers with their 24h volume attached.
+fn spot_entries(response: Response) -> Vec<Entry> {
+    let volumes: HashMap<String, f64> = response
+        .data
+        .ticker
+        .iter()
+        .map(|t| (t.symbol.clone(), t.vol))
+        .collect();
+
+    let mut entries = Entry::from_lines(&get_spot_impl(response));
+    for entry in &mut entries {
+        let pair = entry.symbol.strip_suffix("USDT").unwrap_or(&entry.symbol);
+        entry.volume = volumes.get(&format!("{pair}-USDT")).copied();
+    }
+    entries
+}
+
+#[instrument(name = "kucoin")]
+pub async fn get_spot_entries() -> Vec<Entry> {
+    match get_data().await {
+        Ok(data) => spot_entries(data),
+        Err(e) => {
+            error!(error = %e, "Failed to get data");
+            vec![]
+        }
+    }
+}
+
+#[instrument(name = "kucoin")]
 pub async fn get_spot() -> Vec<String> {
     match get_data().await {
         Ok(data) => get_spot_impl(data),
         Err(e) => {
-            eprintln!("Failed to get data: {e}");
+            error!(error = %e, "Failed to get data");
             vec![]
         }
     }
@@ -243,4 +290,44 @@ mod tests {
             "KuCoin should have ETH"
         );
     }
+
+    #[test]
+    fn test_spot_entries_keep_volume() {
+        let response = Response {
+            data: ResponseData {
+                ticker: vec![
+                    ResponseTicker {
+                        symbol: "BTC-USDT".to_string(),
+                        vol: 100.0,
+                    },
+                    ResponseTicker {
+                        symbol: "XMR-USDT".to_string(),
+                        vol: 5_000.0,
+                    },
+                ],
+            },
+        };
+
+        let summary: Vec<(String, Option<f64>, usize)> = spot_entries(response)
+            .into_iter()
+            .map(|e| (e.ticker, e.volume, e.rank))
+            .collect();
+
+        assert_eq!(
+            summary,
+            vec![
+                ("KUCOIN:XMRUSDT".to_string(), Some(5_000.0), 1),
+                ("KUCOIN:BTCUSDT".to_string(), Some(100.0), 2),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_error_envelope() {
+        let error = parse(r#"{"code":"429000","msg":"Too many requests"}"#)
+            .expect_err("A non-success code should fail");
+
+        assert_eq!(error.to_string(), "KuCoin error 429000: Too many requests");
+        assert!(parse(r#"{"code":"200000","data":{"ticker":[]}}"#).is_ok());
+    }
 }
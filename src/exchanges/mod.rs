@@ -1,8 +1,11 @@
 pub mod binance;
 pub mod coinbase;
 pub mod coingecko;
+pub mod defillama;
 pub mod earningshub;
 pub mod ibkr;
+pub mod indices;
 pub mod kucoin;
+pub mod nasdaq;
 pub mod stockanalysis;
 pub mod woo;
@@ -0,0 +1,403 @@
+use chrono::NaiveDate;
+use color_eyre::eyre::Result;
+use serde_derive::{Deserialize, Serialize};
+use tracing::{instrument, warn};
+
+use crate::calendar::DateRange;
+use crate::earnings::{Earning, Session};
+use crate::events::Event;
+use crate::http;
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct Response {
+    pub data: ResponseData,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct ResponseData {
+    pub rows: Vec<Row>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct Row {
+    pub symbol: String,
+    #[serde(rename = "marketCap", default)]
+    pub market_cap: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Listing {
+    pub symbol: String,
+    pub exchange: String,
+    pub market_cap: Option<f64>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct EarningsResponse {
+    pub data: Option<EarningsData>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct EarningsData {
+    pub rows: Option<Vec<EarningsRow>>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct EarningsRow {
+    pub symbol: String,
+    #[serde(default)]
+    pub time: String,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct DividendsResponse {
+    pub data: Option<DividendsData>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct DividendsData {
+    pub calendar: Option<DividendsCalendar>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct DividendsCalendar {
+    pub rows: Option<Vec<DividendRow>>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct DividendRow {
+    pub symbol: String,
+}
+
+/// Exchanges covered by the screener, as `(query value, TradingView prefix)`.
+const EXCHANGES: [(&str, &str); 3] = [("nasdaq", "NASDAQ"), ("nyse", "NYSE"), ("amex", "AMEX")];
+
+async fn get_data(exchange: &str) -> Result<Response> {
+    let res = http::get(&format!(
+        "https://api.nasdaq.com/api/screener/stocks?tableonly=true&download=true&exchange={exchange}"
+    ))
+    .await?;
+    let parsed: Response = serde_json::from_str(&res)?;
+    Ok(parsed)
+}
+
+async fn get_earnings_data(date: NaiveDate) -> Result<EarningsResponse> {
+    let date = date.format("%Y-%m-%d");
+    let res = http::get(&format!(
+        "https://api.nasdaq.com/api/calendar/earnings?date={date}"
+    ))
+    .await?;
+    let parsed: EarningsResponse = serde_json::from_str(&res)?;
+    Ok(parsed)
+}
+
+async fn get_dividends_data(date: NaiveDate) -> Result<DividendsResponse> {
+    let date = date.format("%Y-%m-%d");
+    let res = http::get(&format!(
+        "https://api.nasdaq.com/api/calendar/dividends?date={date}"
+    ))
+    .await?;
+    let parsed: DividendsResponse = serde_json::from_str(&res)?;
+    Ok(parsed)
+}
+
+fn parse_market_cap(value: &str) -> Option<f64> {
+    let cap = value.replace(',', "").parse::<f64>().ok()?;
+    (cap > 0.0).then_some(cap)
+}
+
+#[must_use]
+pub fn process_data(response: Response, exchange: &str) -> Vec<Listing> {
+    response
+        .data
+        .rows
+        .iter()
+        .map(|row| Listing {
+            symbol: row.symbol.trim().replace('/', "."),
+            exchange: exchange.to_string(),
+            market_cap: parse_market_cap(&row.market_cap),
+        })
+        .filter(|listing| !listing.symbol.is_empty())
+        .collect()
+}
+
+/// Every US-listed stock with its primary exchange and market cap.
+///
+/// # Errors
+///
+/// When the screener of any exchange can't be fetched or decoded.
+#[instrument(name = "nasdaq_screener")]
+pub async fn get_listings() -> Result<Vec<Listing>> {
+    let mut listings = Vec::new();
+    for (query, exchange) in EXCHANGES {
+        let response = get_data(query).await?;
+        listings.extend(process_data(response, exchange));
+    }
+    Ok(listings)
+}
+
+#[must_use]
+pub fn process_earnings(response: EarningsResponse, date: NaiveDate) -> Vec<Earning> {
+    response
+        .data
+        .and_then(|data| data.rows)
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|row| !row.symbol.trim().is_empty())
+        .map(|row| Earning {
+            symbol: row.symbol.trim().replace('/', "."),
+            date: Some(date),
+            session: match row.time.as_str() {
+                "time-pre-market" => Session::BeforeOpen,
+                "time-after-hours" => Session::AfterClose,
+                _ => Session::Unknown,
+            },
+        })
+        .collect()
+}
+
+/// Earnings reported on each weekday of `range`, one request per day.
+#[instrument(name = "nasdaq_earnings", skip_all, fields(from = %range.from, to = %range.to))]
+pub async fn get_earnings(range: &DateRange) -> Vec<Earning> {
+    let mut earnings: Vec<Earning> = Vec::new();
+    for date in range.weekdays() {
+        match get_earnings_data(date).await {
+            Ok(response) => {
+                for earning in process_earnings(response, date) {
+                    if !earnings.iter().any(|e| e.symbol == earning.symbol) {
+                        earnings.push(earning);
+                    }
+                }
+            }
+            Err(e) => warn!(error = %e, %date, "Failed to fetch Nasdaq earnings"),
+        }
+    }
+    earnings
+}
+
+/// Symbols going ex-dividend on `date`, which is the date the calendar was
+/// queried for.
+#[must_use]
+pub fn process_dividends(response: DividendsResponse, date: NaiveDate) -> Vec<Event> {
+    response
+        .data
+        .and_then(|data| data.calendar)
+        .and_then(|calendar| calendar.rows)
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|row| !row.symbol.trim().is_empty())
+        .map(|row| Event {
+            date,
+            symbol: row.symbol.trim().replace('/', "."),
+        })
+        .collect()
+}
+
+/// Ex-dividend dates on each weekday of `range`, one request per day.
+#[instrument(name = "nasdaq_dividends", skip_all, fields(from = %range.from, to = %range.to))]
+pub async fn get_ex_dividends(range: &DateRange) -> Vec<Event> {
+    let mut events = Vec::new();
+    for date in range.weekdays() {
+        match get_dividends_data(date).await {
+            Ok(response) => events.extend(process_dividends(response, date)),
+            Err(e) => warn!(error = %e, %date, "Failed to fetch Nasdaq dividends"),
+        }
+    }
+    events
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_process_data() {
+        let response = Response {
+            data: ResponseData {
+                rows: vec![
+                    Row {
+                        symbol: "AAPL".to_string(),
+                        market_cap: "4,012,321,450,000.00".to_string(),
+                    },
+                    Row {
+                        symbol: "BRK/B".to_string(),
+                        market_cap: "1,045,000,000,000.00".to_string(),
+                    },
+                    Row {
+                        symbol: "ZVZZT".to_string(),
+                        market_cap: String::new(),
+                    },
+                ],
+            },
+        };
+
+        let result = process_data(response, "NASDAQ");
+
+        assert_eq!(
+            result,
+            vec![
+                Listing {
+                    symbol: "AAPL".to_string(),
+                    exchange: "NASDAQ".to_string(),
+                    market_cap: Some(4_012_321_450_000.0),
+                },
+                Listing {
+                    symbol: "BRK.B".to_string(),
+                    exchange: "NASDAQ".to_string(),
+                    market_cap: Some(1_045_000_000_000.0),
+                },
+                Listing {
+                    symbol: "ZVZZT".to_string(),
+                    exchange: "NASDAQ".to_string(),
+                    market_cap: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_get_listings_from_fixture() {
+        let fixture_path = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests")
+            .join("fixtures")
+            .join("nasdaq_screener_nyse.json");
+
+        if !fixture_path.exists() {
+            eprintln!("Skipping test: fixture file not found");
+            return;
+        }
+
+        let fixture_data = std::fs::read_to_string(fixture_path)
+            .expect("Failed to read nasdaq fixture file - file may be corrupted");
+        let response: Response = serde_json::from_str(&fixture_data)
+            .expect("Failed to parse nasdaq fixture JSON - file may be corrupted");
+        let listings = process_data(response, "NYSE");
+
+        assert!(!listings.is_empty());
+        assert!(listings.iter().all(|l| l.exchange == "NYSE"));
+        assert!(listings.iter().all(|l| !l.symbol.contains('/')));
+
+        let jpm = listings
+            .iter()
+            .find(|l| l.symbol == "JPM")
+            .expect("NYSE should list JPM");
+        assert!(jpm.market_cap.is_some_and(|cap| cap > 1e11));
+    }
+
+    #[test]
+    fn test_process_earnings_sessions() {
+        let date = NaiveDate::from_ymd_opt(2025, 10, 30).expect("Invalid date");
+        let response = EarningsResponse {
+            data: Some(EarningsData {
+                rows: Some(vec![
+                    EarningsRow {
+                        symbol: "LLY".to_string(),
+                        time: "time-pre-market".to_string(),
+                    },
+                    EarningsRow {
+                        symbol: "AAPL".to_string(),
+                        time: "time-after-hours".to_string(),
+                    },
+                    EarningsRow {
+                        symbol: "BRK/B".to_string(),
+                        time: "time-not-supplied".to_string(),
+                    },
+                ]),
+            }),
+        };
+
+        let result = process_earnings(response, date);
+        let summary: Vec<(&str, Session)> = result
+            .iter()
+            .map(|e| (e.symbol.as_str(), e.session))
+            .collect();
+
+        assert_eq!(
+            summary,
+            vec![
+                ("LLY", Session::BeforeOpen),
+                ("AAPL", Session::AfterClose),
+                ("BRK.B", Session::Unknown),
+            ]
+        );
+        assert!(result.iter().all(|e| e.date == Some(date)));
+    }
+
+    #[test]
+    fn test_process_earnings_no_rows() {
+        let date = NaiveDate::from_ymd_opt(2025, 11, 1).expect("Invalid date");
+        let response: EarningsResponse = serde_json::from_str(
+            r#"{"data":{"asOf":"Sat, Nov 1, 2025","rows":null},"status":{"rCode":200}}"#,
+        )
+        .expect("Failed to parse earnings JSON");
+
+        assert!(process_earnings(response, date).is_empty());
+    }
+
+    #[test]
+    fn test_get_earnings_from_fixture() {
+        let fixture_path = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests")
+            .join("fixtures")
+            .join("nasdaq_earnings.json");
+
+        if !fixture_path.exists() {
+            eprintln!("Skipping test: fixture file not found");
+            return;
+        }
+
+        let fixture_data = std::fs::read_to_string(fixture_path)
+            .expect("Failed to read nasdaq earnings fixture file - file may be corrupted");
+        let response: EarningsResponse = serde_json::from_str(&fixture_data)
+            .expect("Failed to parse nasdaq earnings fixture JSON - file may be corrupted");
+        let date = NaiveDate::from_ymd_opt(2025, 10, 30).expect("Invalid date");
+        let earnings = process_earnings(response, date);
+
+        assert!(!earnings.is_empty());
+        assert!(earnings.iter().any(|e| e.session == Session::BeforeOpen));
+        assert!(earnings.iter().any(|e| e.session == Session::AfterClose));
+        assert!(
+            earnings
+                .iter()
+                .any(|e| e.symbol == "AAPL" && e.session == Session::AfterClose),
+            "Apple should report after close"
+        );
+    }
+
+    #[test]
+    fn test_process_dividends() {
+        let date = NaiveDate::from_ymd_opt(2025, 11, 10).expect("Invalid date");
+        let response: DividendsResponse = serde_json::from_str(
+            r#"{"data":{"calendar":{"headers":{},"rows":[
+                {"companyName":"Apple Inc.","symbol":"AAPL","dividend_Ex_Date":"11/10/2025"},
+                {"companyName":"Brown Forman Inc","symbol":"BF/B","dividend_Ex_Date":"11/10/2025"}
+            ]}},"status":{"rCode":200}}"#,
+        )
+        .expect("Failed to parse dividends JSON");
+
+        assert_eq!(
+            process_dividends(response, date),
+            vec![
+                Event {
+                    date,
+                    symbol: "AAPL".to_string(),
+                },
+                Event {
+                    date,
+                    symbol: "BF.B".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_process_dividends_no_calendar() {
+        let date = NaiveDate::from_ymd_opt(2025, 11, 8).expect("Invalid date");
+        let response: DividendsResponse =
+            serde_json::from_str(r#"{"data":{"calendar":{"rows":null}},"status":{"rCode":200}}"#)
+                .expect("Failed to parse dividends JSON");
+
+        assert!(process_dividends(response, date).is_empty());
+    }
+}
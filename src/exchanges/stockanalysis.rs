@@ -1,28 +1,49 @@
 use color_eyre::eyre::Result;
-use reqwest::header;
 use scraper::{Html, Selector};
+use serde_json::Value;
+use tracing::{instrument, warn};
+
+use crate::http;
 
 async fn get_html(ticker: &str) -> Result<String> {
-    let api_url = format!("https://stockanalysis.com/etf/{ticker}/holdings");
-    let client = reqwest::Client::new();
-    let res = client
-        .get(&api_url)
-        .header(
-            header::USER_AGENT,
-            "Mozilla/5.0 (Windows NT 10.0; Win64; x64; rv:109.0) Gecko/20100101 Firefox/113.0",
-        )
-        .send()
-        .await?
-        .text()
-        .await?;
-
-    Ok(res)
+    Ok(http::get(&format!("https://stockanalysis.com/etf/{ticker}/holdings")).await?)
 }
 
-pub fn parse_html(html: &str) -> Result<Vec<String>> {
+/// The page's SvelteKit data endpoint, which carries the full holdings
+/// list rather than the first page rendered into the table.
+async fn get_data(ticker: &str) -> Result<String> {
+    let ticker = ticker.to_lowercase();
+    Ok(http::get(&format!(
+        "https://stockanalysis.com/etf/{ticker}/holdings/__data.json?x-sveltekit-trailing-slash=1&x-sveltekit-invalidated=001"
+    ))
+    .await?)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Holding {
+    pub symbol: String,
+    pub name: String,
+    pub weight: Option<f64>,
+}
+
+fn parse_weight(text: &str) -> Option<f64> {
+    text.trim().strip_suffix('%')?.trim().parse::<f64>().ok()
+}
+
+/// Holdings in the table of a fund's holdings page, which only lists the
+/// first page of them.
+///
+/// # Errors
+///
+/// When the page has no holdings table.
+pub fn parse_holdings(html: &str) -> Result<Vec<Holding>> {
     let document = Html::parse_document(html);
     let table_selector = Selector::parse("#main table tbody")
         .map_err(|e| color_eyre::eyre::eyre!("Failed to parse table selector: {:?}", e))?;
+    let row_selector = Selector::parse("tr")
+        .map_err(|e| color_eyre::eyre::eyre!("Failed to parse row selector: {:?}", e))?;
+    let cell_selector = Selector::parse("td")
+        .map_err(|e| color_eyre::eyre::eyre!("Failed to parse cell selector: {:?}", e))?;
     let ticker_selector = Selector::parse("td a")
         .map_err(|e| color_eyre::eyre::eyre!("Failed to parse ticker selector: {:?}", e))?;
 
@@ -31,15 +52,163 @@ pub fn parse_html(html: &str) -> Result<Vec<String>> {
         .next()
         .ok_or_else(|| color_eyre::eyre::eyre!("Table not found in HTML"))?;
 
-    let ticker_els = data.select(&ticker_selector);
-    let tickers: Vec<String> = ticker_els.map(|el| el.text().collect::<String>()).collect();
+    let holdings = data
+        .select(&row_selector)
+        .filter_map(|row| {
+            let symbol = row
+                .select(&ticker_selector)
+                .next()?
+                .text()
+                .collect::<String>();
+            let cells: Vec<String> = row
+                .select(&cell_selector)
+                .map(|cell| cell.text().collect::<String>().trim().to_string())
+                .collect();
+            let weight = cells.iter().find_map(|cell| parse_weight(cell));
+            let name = cells
+                .iter()
+                .find(|cell| {
+                    !cell.is_empty()
+                        && **cell != symbol
+                        && parse_weight(cell).is_none()
+                        && !cell.chars().all(|c| c.is_ascii_digit() || c == ',')
+                })
+                .cloned()
+                .unwrap_or_default();
+
+            Some(Holding {
+                symbol,
+                name,
+                weight,
+            })
+        })
+        .collect();
+
+    Ok(holdings)
+}
+
+/// Symbols in the table of a fund's holdings page.
+///
+/// # Errors
+///
+/// When the page has no holdings table.
+pub fn parse_html(html: &str) -> Result<Vec<String>> {
+    let holdings = parse_holdings(html)?;
+    Ok(holdings.into_iter().map(|h| h.symbol).collect())
+}
+
+/// Resolves a value from a `devalue`-encoded array, where objects and arrays
+/// reference their members by index into the same array.
+fn hydrate(values: &[Value], index: usize, depth: usize) -> Value {
+    if depth > 32 {
+        return Value::Null;
+    }
+    let resolve = |value: &Value| {
+        value
+            .as_u64()
+            .and_then(|i| usize::try_from(i).ok())
+            .map_or(Value::Null, |i| hydrate(values, i, depth.saturating_add(1)))
+    };
 
-    Ok(tickers)
+    match values.get(index) {
+        Some(Value::Object(map)) => Value::Object(
+            map.iter()
+                .map(|(key, value)| (key.clone(), resolve(value)))
+                .collect(),
+        ),
+        Some(Value::Array(items)) if !items.first().is_some_and(Value::is_string) => {
+            Value::Array(items.iter().map(resolve).collect())
+        }
+        Some(value) => value.clone(),
+        None => Value::Null,
+    }
 }
 
-pub async fn get_components(ticker: &str) -> Result<Vec<String>> {
-    let html = get_html(ticker).await?;
-    parse_html(&html)
+fn parse_data_holding(value: &Value) -> Option<Holding> {
+    let symbol = value
+        .get("s")?
+        .as_str()?
+        .trim_start_matches(|c: char| !c.is_ascii_alphanumeric());
+    if symbol.is_empty() {
+        return None;
+    }
+
+    Some(Holding {
+        symbol: symbol.to_string(),
+        name: value
+            .get("n")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string(),
+        weight: value
+            .get("as")
+            .and_then(Value::as_str)
+            .and_then(parse_weight),
+    })
+}
+
+/// Parses the `__data.json` response into holdings and the fund's declared
+/// total holding count.
+///
+/// # Errors
+///
+/// When the response isn't JSON or holds no holdings list.
+pub fn parse_data(json: &str) -> Result<(Vec<Holding>, Option<usize>)> {
+    let response: Value = serde_json::from_str(json)?;
+    let nodes = response
+        .get("nodes")
+        .and_then(Value::as_array)
+        .ok_or_else(|| color_eyre::eyre::eyre!("Data response has no nodes"))?;
+
+    let data = nodes
+        .iter()
+        .filter_map(|node| node.get("data").and_then(Value::as_array))
+        .map(|values| hydrate(values, 0, 0))
+        .find(|data| data.get("holdings").is_some_and(Value::is_array))
+        .ok_or_else(|| color_eyre::eyre::eyre!("Holdings not found in data response"))?;
+
+    let holdings = data
+        .get("holdings")
+        .and_then(Value::as_array)
+        .map(|rows| rows.iter().filter_map(parse_data_holding).collect())
+        .unwrap_or_default();
+    let count = data
+        .get("count")
+        .and_then(Value::as_u64)
+        .and_then(|c| usize::try_from(c).ok());
+
+    Ok((holdings, count))
+}
+
+/// Every holding of the ETF `ticker`, from the data endpoint when it
+/// answers and the holdings page otherwise.
+///
+/// # Errors
+///
+/// When neither the data endpoint nor the holdings page can be fetched
+/// and parsed.
+#[instrument(name = "stockanalysis")]
+pub async fn get_holdings(ticker: &str) -> Result<Vec<Holding>> {
+    let from_data = get_data(ticker).await.and_then(|json| parse_data(&json));
+
+    let (holdings, count) = match from_data {
+        Ok((holdings, count)) if !holdings.is_empty() => (holdings, count),
+        Ok(_) => (parse_holdings(&get_html(ticker).await?)?, None),
+        Err(e) => {
+            warn!(error = %e, "Data endpoint unavailable, falling back to HTML table");
+            (parse_holdings(&get_html(ticker).await?)?, None)
+        }
+    };
+
+    if let Some(count) = count.filter(|count| *count > holdings.len()) {
+        warn!(
+            available = holdings.len(),
+            total = count,
+            "Holdings list is incomplete"
+        );
+    }
+
+    Ok(holdings)
 }
 
 #[cfg(test)]
@@ -156,4 +325,168 @@ mod tests {
             "SPY should contain NVDA"
         );
     }
+
+    #[test]
+    fn test_parse_holdings_weights() {
+        let html = r#"
+            <html>
+                <body>
+                    <div id="main">
+                        <table>
+                            <tbody>
+                                <tr>
+                                    <td>1</td>
+                                    <td><a href="/stocks/nvda/">NVDA</a></td>
+                                    <td>NVIDIA Corporation</td>
+                                    <td>8.50%</td>
+                                    <td>289,644,069</td>
+                                </tr>
+                                <tr>
+                                    <td>2</td>
+                                    <td><a href="/stocks/aapl/">AAPL</a></td>
+                                    <td>Apple Inc.</td>
+                                    <td>6.92%</td>
+                                    <td>176,164,569</td>
+                                </tr>
+                            </tbody>
+                        </table>
+                    </div>
+                </body>
+            </html>
+        "#;
+
+        let holdings = parse_holdings(html).expect("Failed to parse holdings HTML");
+
+        assert_eq!(
+            holdings,
+            vec![
+                Holding {
+                    symbol: "NVDA".to_string(),
+                    name: "NVIDIA Corporation".to_string(),
+                    weight: Some(8.5),
+                },
+                Holding {
+                    symbol: "AAPL".to_string(),
+                    name: "Apple Inc.".to_string(),
+                    weight: Some(6.92),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_get_holdings_from_fixture() {
+        let fixture_path = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests")
+            .join("fixtures")
+            .join("stockanalysis_spy.html");
+
+        if !fixture_path.exists() {
+            eprintln!("Skipping test: fixture file not found");
+            return;
+        }
+
+        let html = std::fs::read_to_string(fixture_path)
+            .expect("Failed to read stockanalysis fixture file - file may be corrupted");
+        let holdings = parse_holdings(&html).expect(
+            "Failed to parse stockanalysis HTML - file may be corrupted or HTML structure changed",
+        );
+
+        assert!(holdings.iter().all(|h| h.weight.is_some()));
+
+        let nvda = holdings
+            .iter()
+            .find(|h| h.symbol == "NVDA")
+            .expect("SPY should contain NVDA");
+        assert_eq!(nvda.name, "NVIDIA Corporation");
+        assert_eq!(nvda.weight, Some(8.5));
+    }
+
+    #[test]
+    fn test_parse_data_basic() {
+        let json = r#"{
+            "type": "data",
+            "nodes": [
+                {"type": "skip"},
+                {
+                    "type": "data",
+                    "data": [
+                        {"holdings": 1, "count": 10},
+                        [2, 6],
+                        {"n": 3, "s": 4, "as": 5},
+                        "NVIDIA Corporation",
+                        "$NVDA",
+                        "8.50%",
+                        {"n": 7, "s": 8, "as": 9},
+                        "Apple Inc.",
+                        "$AAPL",
+                        "6.92%",
+                        3
+                    ]
+                }
+            ]
+        }"#;
+
+        let (holdings, count) = parse_data(json).expect("Failed to parse data response");
+
+        assert_eq!(count, Some(3));
+        assert_eq!(
+            holdings,
+            vec![
+                Holding {
+                    symbol: "NVDA".to_string(),
+                    name: "NVIDIA Corporation".to_string(),
+                    weight: Some(8.5),
+                },
+                Holding {
+                    symbol: "AAPL".to_string(),
+                    name: "Apple Inc.".to_string(),
+                    weight: Some(6.92),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_data_missing_holdings() {
+        let json = r#"{"type": "data", "nodes": [{"type": "data", "data": [{"info": 1}, "spy"]}]}"#;
+
+        assert!(parse_data(json).is_err());
+    }
+
+    #[test]
+    fn test_get_holdings_from_data_fixture() {
+        let fixtures = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests")
+            .join("fixtures");
+        let data_path = fixtures.join("stockanalysis_spy_data.json");
+        let html_path = fixtures.join("stockanalysis_spy.html");
+
+        if !data_path.exists() || !html_path.exists() {
+            eprintln!("Skipping test: fixture file not found");
+            return;
+        }
+
+        let json = std::fs::read_to_string(data_path)
+            .expect("Failed to read stockanalysis data fixture file - file may be corrupted");
+        let (holdings, count) = parse_data(&json).expect(
+            "Failed to parse stockanalysis data JSON - file may be corrupted or format changed",
+        );
+        let html = std::fs::read_to_string(html_path)
+            .expect("Failed to read stockanalysis fixture file - file may be corrupted");
+        let first_page = parse_holdings(&html).expect(
+            "Failed to parse stockanalysis HTML - file may be corrupted or HTML structure changed",
+        );
+
+        assert!(
+            holdings.len() > first_page.len(),
+            "Data endpoint should return more holdings than the first page"
+        );
+        assert!(count.is_some_and(|count| count >= holdings.len()));
+        assert!(holdings.iter().all(|h| !h.symbol.starts_with('$')));
+        assert!(holdings.iter().all(|h| h.weight.is_some()));
+        assert!(first_page
+            .iter()
+            .all(|h| holdings.iter().any(|d| d.symbol == h.symbol)));
+    }
 }
@@ -1,13 +1,41 @@
-use reqwest::header;
 use serde_derive::{Deserialize, Serialize};
+use tracing::{error, instrument};
+
+use crate::http;
+use crate::output::{self, Entry};
 
 #[derive(Deserialize, Serialize, Debug)]
 pub struct Response {
     pub rows: Vec<Row>,
-    #[allow(dead_code)]
     pub success: bool,
 }
 
+/// WOO reports failures as `success: false` with a numeric `code` and a
+/// `message`, sometimes with a 200 status.
+#[derive(Deserialize, Debug)]
+struct Envelope {
+    success: bool,
+    #[serde(default)]
+    code: Option<i64>,
+    #[serde(default)]
+    message: String,
+}
+
+fn api_error(body: &str) -> Option<String> {
+    let envelope: Envelope = serde_json::from_str(body).ok()?;
+    (!envelope.success).then(|| match envelope.code {
+        Some(code) => format!("WOO error {code}: {}", envelope.message),
+        None => format!("WOO error: {}", envelope.message),
+    })
+}
+
+fn parse(body: &str) -> Result<Response, Box<dyn std::error::Error>> {
+    if let Some(error) = api_error(body) {
+        return Err(error.into());
+    }
+    Ok(serde_json::from_str(body)?)
+}
+
 #[derive(Deserialize, Serialize, Debug)]
 pub struct Row {
     pub symbol: String,
@@ -19,21 +47,9 @@ const EXCHANGE_NAME: &str = "WOONETWORK";
 
 async fn get_data() -> Result<Response, Box<dyn std::error::Error>> {
     let api_url = "https://api.woo.org/v1/public/info";
-    let client = reqwest::Client::new();
-    let res = client
-        .get(api_url)
-        .header(
-            header::USER_AGENT,
-            "Mozilla/5.0 (Windows NT 10.0; Win64; x64; rv:109.0) Gecko/20100101 Firefox/113.0",
-        )
-        .send()
-        .await?
-        .text()
-        .await?;
-
-    let parsed: Response = serde_json::from_str(&res)?;
-
-    Ok(parsed)
+    let res = http::get(api_url).await.map_err(|e| e.decode(api_error))?;
+
+    parse(&res)
 }
 
 fn filter_symbols(response: Vec<Row>) -> Vec<String> {
@@ -44,14 +60,19 @@ fn filter_symbols(response: Vec<Row>) -> Vec<String> {
         .collect()
 }
 
+#[instrument(name = "woo")]
 async fn get_symbols() -> Vec<String> {
     let data = get_data().await;
     match data {
         Ok(data) => filter_symbols(data.rows),
-        Err(_) => vec![],
+        Err(e) => {
+            error!(error = %e, "Failed to get data");
+            vec![]
+        }
     }
 }
 
+#[must_use]
 pub fn process_perp(symbols: &[String]) -> Vec<String> {
     symbols
         .iter()
@@ -68,6 +89,7 @@ pub fn process_perp(symbols: &[String]) -> Vec<String> {
         .collect()
 }
 
+#[must_use]
 pub fn process_spot(symbols: &[String]) -> Vec<String> {
     symbols
         .iter()
@@ -94,6 +116,31 @@ pub async fn get_spot() -> Vec<String> {
     process_spot(&data)
 }
 
+/// The requested markets with the source part of their file name. Spot and
+/// perps go into one watchlist with a section each when both are asked for.
+pub async fn get_entries(perp: bool, spot: bool) -> Option<(&'static str, Vec<Entry>)> {
+    let spot = if spot {
+        Some(Entry::from_lines(&get_spot().await))
+    } else {
+        None
+    };
+    let perp = if perp {
+        Some(Entry::from_lines(&get_perp().await))
+    } else {
+        None
+    };
+
+    match (spot, perp) {
+        (Some(spot), Some(perp)) => Some((
+            "WOO",
+            output::sectioned(vec![("Spot", spot), ("Perps", perp)]),
+        )),
+        (Some(spot), None) => Some(("WOO-SPOT", spot)),
+        (None, Some(perp)) => Some(("WOO-PERP", perp)),
+        (None, None) => None,
+    }
+}
+
 #[cfg(test)]
 #[allow(clippy::expect_used)]
 mod tests {
@@ -228,4 +275,13 @@ mod tests {
             "WOO should have ETH spot"
         );
     }
+
+    #[test]
+    fn test_parse_error_envelope() {
+        let error = parse(r#"{"success":false,"code":-1003,"message":"Rate limit exceed."}"#)
+            .expect_err("success: false should fail");
+
+        assert_eq!(error.to_string(), "WOO error -1003: Rate limit exceed.");
+        assert!(parse(r#"{"success":true,"rows":[]}"#).is_ok());
+    }
 }
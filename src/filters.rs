@@ -0,0 +1,180 @@
+use std::collections::HashSet;
+use std::io;
+use std::path::PathBuf;
+
+use crate::exchanges::nasdaq::Listing;
+use crate::output::{self, Format};
+use crate::utils;
+
+/// Parses a market cap such as `2B`, `500M` or `1500000000`.
+///
+/// # Errors
+///
+/// When the value isn't a number with an optional K, M, B or T suffix.
+pub fn parse_market_cap(value: &str) -> Result<f64, String> {
+    let value = value.trim();
+    let (number, multiplier) = match value.chars().last().map(|c| c.to_ascii_uppercase()) {
+        Some('K') => (value.get(..value.len().saturating_sub(1)), 1e3),
+        Some('M') => (value.get(..value.len().saturating_sub(1)), 1e6),
+        Some('B') => (value.get(..value.len().saturating_sub(1)), 1e9),
+        Some('T') => (value.get(..value.len().saturating_sub(1)), 1e12),
+        _ => (Some(value), 1.0),
+    };
+
+    number
+        .and_then(|n| n.trim().parse::<f64>().ok())
+        .map(|n| n * multiplier)
+        .ok_or_else(|| format!("invalid market cap: {value}"))
+}
+
+/// The bare symbol of a watchlist entry, without its `EXCHANGE:` prefix.
+#[must_use]
+pub fn base_symbol(ticker: &str) -> &str {
+    ticker.rsplit(':').next().unwrap_or(ticker)
+}
+
+/// Symbols listed in the given watchlist files, in any format `output`
+/// writes, ignoring section headers.
+///
+/// # Errors
+///
+/// When one of the files can't be read or its extension isn't a known
+/// format.
+pub fn read_universe(paths: &[PathBuf]) -> io::Result<HashSet<String>> {
+    let mut universe = HashSet::new();
+    for path in paths {
+        let format = Format::from_path(path).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "Unknown watchlist format: {}, expected .txt, .csv or .json",
+                    path.display()
+                ),
+            )
+        })?;
+        for ticker in output::keys(&utils::read_file(path)?, format) {
+            universe.insert(base_symbol(&ticker).to_string());
+        }
+    }
+    Ok(universe)
+}
+
+pub struct ListingFilter {
+    pub min_market_cap: Option<f64>,
+    pub exchanges: Vec<String>,
+}
+
+impl ListingFilter {
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.min_market_cap.is_none() && self.exchanges.is_empty()
+    }
+
+    /// Whether `symbol` passes the filter. Symbols without a listing are
+    /// rejected since neither their market cap nor exchange is known.
+    #[must_use]
+    pub fn matches(&self, symbol: &str, listings: &[Listing]) -> bool {
+        let Some(listing) = listings.iter().find(|l| l.symbol == symbol) else {
+            return false;
+        };
+
+        let cap_ok = self
+            .min_market_cap
+            .is_none_or(|min| listing.market_cap.is_some_and(|cap| cap >= min));
+        let exchange_ok = self.exchanges.is_empty()
+            || self
+                .exchanges
+                .iter()
+                .any(|e| e.eq_ignore_ascii_case(&listing.exchange));
+
+        cap_ok && exchange_ok
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    fn listings() -> Vec<Listing> {
+        vec![
+            Listing {
+                symbol: "AAPL".to_string(),
+                exchange: "NASDAQ".to_string(),
+                market_cap: Some(4e12),
+            },
+            Listing {
+                symbol: "VMEO".to_string(),
+                exchange: "NASDAQ".to_string(),
+                market_cap: Some(1.3e9),
+            },
+            Listing {
+                symbol: "JPM".to_string(),
+                exchange: "NYSE".to_string(),
+                market_cap: Some(8.5e11),
+            },
+        ]
+    }
+
+    #[test]
+    fn test_parse_market_cap() {
+        assert_eq!(parse_market_cap("2B"), Ok(2e9));
+        assert_eq!(parse_market_cap("500m"), Ok(5e8));
+        assert_eq!(parse_market_cap("1.5T"), Ok(1.5e12));
+        assert_eq!(parse_market_cap("1000000"), Ok(1e6));
+        assert!(parse_market_cap("big").is_err());
+    }
+
+    #[test]
+    fn test_base_symbol() {
+        assert_eq!(base_symbol("NASDAQ:AAPL"), "AAPL");
+        assert_eq!(base_symbol("AAPL"), "AAPL");
+    }
+
+    #[test]
+    fn test_listing_filter_market_cap() {
+        let filter = ListingFilter {
+            min_market_cap: Some(2e9),
+            exchanges: Vec::new(),
+        };
+
+        assert!(filter.matches("AAPL", &listings()));
+        assert!(!filter.matches("VMEO", &listings()));
+        assert!(!filter.matches("UNKNOWN", &listings()));
+    }
+
+    #[test]
+    fn test_listing_filter_exchange() {
+        let filter = ListingFilter {
+            min_market_cap: None,
+            exchanges: vec!["nyse".to_string()],
+        };
+
+        assert!(filter.matches("JPM", &listings()));
+        assert!(!filter.matches("AAPL", &listings()));
+    }
+
+    #[test]
+    fn test_read_universe_from_every_format() {
+        let dir = std::env::temp_dir().join(format!("watchlist-{}-universe", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let entries = crate::output::Entry::from_lines(&[
+            "###Tech".to_string(),
+            "NASDAQ:AAPL".to_string(),
+            "NYSE:JPM".to_string(),
+        ]);
+
+        for format in [Format::Tv, Format::Csv, Format::Json, Format::Plain] {
+            let path = dir.join(format!("universe.{}", format.extension()));
+            utils::write_file(&output::render(&entries, format), &path).expect("Failed to write");
+
+            assert_eq!(
+                read_universe(&[path]).expect("Failed to read universe"),
+                HashSet::from(["AAPL".to_string(), "JPM".to_string()]),
+                "{format:?}"
+            );
+        }
+        assert!(read_universe(&[dir.join("universe.xlsx")]).is_err());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,532 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use reqwest::{header, StatusCode};
+use tracing::{debug, warn};
+
+use crate::cache::{self, Cache};
+
+pub const DEFAULT_USER_AGENT: &str =
+    "Mozilla/5.0 (Windows NT 10.0; Win64; x64; rv:109.0) Gecko/20100101 Firefox/113.0";
+
+/// Longest `Retry-After` honoured, so a misbehaving server can't stall a run.
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub user_agent: String,
+    /// Proxy URL for all requests, e.g. `http://localhost:8080` or
+    /// `socks5://localhost:1080`
+    pub proxy: Option<String>,
+    /// Timeout for establishing a connection
+    pub connect_timeout: Duration,
+    /// Timeout for the whole request, including reading the body
+    pub timeout: Duration,
+    /// Extra attempts after a 429, a 5xx, a timeout or a connection error
+    pub retries: u32,
+    /// Delay before the first retry, doubled on each further one
+    pub backoff: Duration,
+    /// Minimum time between two requests to the same host
+    pub min_interval: Duration,
+    /// Responses reused while fresh instead of fetching again
+    pub cache: Option<Cache>,
+    /// Never touch the network: answer from the cache, however old, or from
+    /// the fixture in `fixtures` for the URL
+    pub offline: bool,
+    pub fixtures: PathBuf,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+            proxy: None,
+            connect_timeout: Duration::from_secs(10),
+            timeout: Duration::from_secs(30),
+            retries: 3,
+            backoff: Duration::from_millis(500),
+            min_interval: Duration::from_millis(250),
+            cache: None,
+            offline: false,
+            fixtures: PathBuf::from("tests/fixtures"),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum Error {
+    Request(reqwest::Error),
+    /// A response outside the 2xx range. `message` is the reason decoded
+    /// from the body, when the source's error format is known.
+    Status {
+        url: String,
+        status: StatusCode,
+        body: String,
+        message: Option<String>,
+    },
+    /// `--offline` and neither a cached response nor a fixture
+    Offline(String),
+}
+
+impl Error {
+    /// Fills in the reason for a failed response using `decode`, which reads
+    /// a source's error body, e.g. Binance's `{"code": 0, "msg": ".."}`.
+    #[must_use]
+    pub fn decode(self, decode: impl FnOnce(&str) -> Option<String>) -> Self {
+        match self {
+            Self::Status {
+                url,
+                status,
+                body,
+                message: None,
+            } => {
+                let message = decode(&body);
+                Self::Status {
+                    url,
+                    status,
+                    body,
+                    message,
+                }
+            }
+            other => other,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Request(e) => write!(f, "{e}"),
+            Self::Status {
+                url,
+                status,
+                message,
+                ..
+            } => {
+                write!(f, "{url} returned {status}")?;
+                if *status == StatusCode::UNAVAILABLE_FOR_LEGAL_REASONS {
+                    write!(f, " (blocked in this region, try --proxy)")?;
+                }
+                match message {
+                    Some(message) => write!(f, ": {message}"),
+                    None => Ok(()),
+                }
+            }
+            Self::Offline(url) => write!(f, "{url} is neither cached nor a fixture (--offline)"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Request(e) => Some(e),
+            Self::Status { .. } | Self::Offline(_) => None,
+        }
+    }
+}
+
+impl From<reqwest::Error> for Error {
+    fn from(e: reqwest::Error) -> Self {
+        Self::Request(e)
+    }
+}
+
+pub struct Http {
+    client: reqwest::Client,
+    config: Config,
+    /// Earliest time the next request to each host may start.
+    next_slot: Mutex<HashMap<String, Instant>>,
+}
+
+static HTTP: OnceLock<Http> = OnceLock::new();
+
+tokio::task_local! {
+    /// Set while running a future passed to `fresh`.
+    static FRESH: bool;
+}
+
+/// Sets up the client shared by every source. Only the first call has an
+/// effect; sources used before it get the default configuration.
+///
+/// # Errors
+///
+/// When the client can't be built, e.g. for an invalid proxy URL.
+pub fn init(config: Config) -> reqwest::Result<()> {
+    let http = Http::new(config)?;
+    let _ = HTTP.set(http);
+    Ok(())
+}
+
+fn shared() -> &'static Http {
+    HTTP.get_or_init(|| {
+        Http::new(Config::default()).unwrap_or_else(|_| Http {
+            client: reqwest::Client::new(),
+            config: Config::default(),
+            next_slot: Mutex::new(HashMap::new()),
+        })
+    })
+}
+
+/// Fetches `url` with the shared client and returns the body of a 2xx
+/// response.
+///
+/// # Errors
+///
+/// See [`Http::get_text`].
+pub async fn get(url: &str) -> Result<String, Error> {
+    shared().get_text(url).await
+}
+
+/// Runs `future` with cached responses ignored, so every request it makes
+/// is fetched. Responses are still cached for later runs.
+pub async fn fresh<F: std::future::Future>(future: F) -> F::Output {
+    FRESH.scope(true, future).await
+}
+
+fn is_retryable(status: StatusCode) -> bool {
+    status.as_u16() == 429 || status.is_server_error()
+}
+
+fn retry_after(response: &reqwest::Response) -> Option<Duration> {
+    response
+        .headers()
+        .get(header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse()
+        .ok()
+        .map(|secs| Duration::from_secs(secs).min(MAX_RETRY_AFTER))
+}
+
+impl Http {
+    /// A client for `config`.
+    ///
+    /// # Errors
+    ///
+    /// When the client can't be built, e.g. for an invalid proxy URL.
+    pub fn new(config: Config) -> reqwest::Result<Self> {
+        let mut builder = reqwest::Client::builder()
+            .user_agent(&config.user_agent)
+            .connect_timeout(config.connect_timeout)
+            .timeout(config.timeout);
+        if let Some(proxy) = &config.proxy {
+            builder = builder.proxy(reqwest::Proxy::all(proxy)?);
+        }
+
+        Ok(Self {
+            client: builder.build()?,
+            config,
+            next_slot: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Waits until `url`'s host may be queried again and books the next slot.
+    async fn wait_for_slot(&self, url: &str) {
+        let host = reqwest::Url::parse(url)
+            .ok()
+            .and_then(|u| u.host_str().map(str::to_string))
+            .unwrap_or_default();
+
+        let wait = {
+            let Ok(mut slots) = self.next_slot.lock() else {
+                return;
+            };
+            let now = Instant::now();
+            let slot = slots
+                .get(&host)
+                .copied()
+                .filter(|slot| *slot > now)
+                .unwrap_or(now);
+            let next = slot.checked_add(self.config.min_interval).unwrap_or(slot);
+            slots.insert(host, next);
+            slot.saturating_duration_since(now)
+        };
+
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    const fn backoff(&self, attempt: u32) -> Duration {
+        self.config
+            .backoff
+            .saturating_mul(2_u32.saturating_pow(attempt))
+    }
+
+    /// Answers from the cache or fixtures when possible, otherwise fetches
+    /// `url` and caches a successful response.
+    ///
+    /// # Errors
+    ///
+    /// When the request fails after all retries, the response isn't a 2xx or,
+    /// with `offline`, nothing stands in for `url`.
+    pub async fn get_text(&self, url: &str) -> Result<String, Error> {
+        let cache = self.config.cache.as_ref();
+        if self.config.offline {
+            return cache
+                .and_then(|cache| cache.get_stale(url))
+                .or_else(|| cache::fixture(&self.config.fixtures, url))
+                .ok_or_else(|| Error::Offline(url.to_string()));
+        }
+        let fresh = FRESH.try_with(|fresh| *fresh).unwrap_or(false);
+        if let Some(body) = cache.filter(|_| !fresh).and_then(|cache| cache.get(url)) {
+            debug!(url, "Using cached response");
+            return Ok(body);
+        }
+
+        let body = self.fetch(url).await?;
+        if let Some(cache) = cache {
+            if let Err(e) = cache.put(url, &body) {
+                warn!(error = %e, url, "Failed to cache response");
+            }
+        }
+        Ok(body)
+    }
+
+    async fn fetch(&self, url: &str) -> Result<String, Error> {
+        let mut attempt = 0;
+        loop {
+            self.wait_for_slot(url).await;
+            let result = self.client.get(url).send().await;
+
+            let delay = match &result {
+                Ok(response) if is_retryable(response.status()) => {
+                    Some(retry_after(response).unwrap_or_else(|| self.backoff(attempt)))
+                }
+                Err(e) if e.is_timeout() || e.is_connect() => Some(self.backoff(attempt)),
+                _ => None,
+            };
+
+            match delay {
+                Some(delay) if attempt < self.config.retries => {
+                    debug!(url, attempt, ?delay, "Retrying");
+                    tokio::time::sleep(delay).await;
+                    attempt = attempt.saturating_add(1);
+                }
+                _ => {
+                    let response = result?;
+                    let status = response.status();
+                    let body = response.text().await?;
+                    debug!(url, %status, bytes = body.len(), "Fetched");
+                    if !status.is_success() {
+                        return Err(Error::Status {
+                            url: url.to_string(),
+                            status,
+                            body,
+                            message: None,
+                        });
+                    }
+                    return Ok(body);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn config() -> Config {
+        Config {
+            backoff: Duration::from_millis(1),
+            min_interval: Duration::ZERO,
+            ..Config::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_retries_server_errors() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/flaky"))
+            .respond_with(ResponseTemplate::new(503))
+            .up_to_n_times(2)
+            .expect(2)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/flaky"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("ok"))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let http = Http::new(config()).expect("Failed to build client");
+        let body = http
+            .get_text(&format!("{}/flaky", server.uri()))
+            .await
+            .expect("Request failed");
+
+        assert_eq!(body, "ok");
+    }
+
+    #[tokio::test]
+    async fn test_gives_up_after_retries() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(429).set_body_string("slow down"))
+            .expect(2)
+            .mount(&server)
+            .await;
+
+        let http = Http::new(Config {
+            retries: 1,
+            ..config()
+        })
+        .expect("Failed to build client");
+        let error = http
+            .get_text(&server.uri())
+            .await
+            .expect_err("A 429 should fail once retries run out");
+
+        assert!(matches!(
+            error,
+            Error::Status { status: StatusCode::TOO_MANY_REQUESTS, ref body, .. } if body == "slow down"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_decodes_error_body() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(
+                ResponseTemplate::new(451).set_body_string(r#"{"msg": "restricted location"}"#),
+            )
+            .mount(&server)
+            .await;
+
+        let http = Http::new(config()).expect("Failed to build client");
+        let error = http
+            .get_text(&server.uri())
+            .await
+            .expect_err("A 451 should fail")
+            .decode(|body| {
+                serde_json::from_str::<serde_json::Value>(body)
+                    .ok()?
+                    .get("msg")?
+                    .as_str()
+                    .map(str::to_string)
+            });
+
+        let message = error.to_string();
+        assert!(message.contains("451 Unavailable For Legal Reasons"));
+        assert!(message.ends_with(": restricted location"));
+    }
+
+    #[tokio::test]
+    async fn test_sends_configured_user_agent() {
+        let server = MockServer::start().await;
+        Mock::given(header("user-agent", "watchlist-test"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("ok"))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let http = Http::new(Config {
+            user_agent: "watchlist-test".to_string(),
+            ..config()
+        })
+        .expect("Failed to build client");
+
+        assert_eq!(
+            http.get_text(&server.uri()).await.expect("Request failed"),
+            "ok"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_reuses_cached_response() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("fresh"))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let cache = Cache {
+            dir: std::env::temp_dir().join(format!("watchlist-{}-http-cache", std::process::id())),
+            ttl: Duration::from_secs(60),
+        };
+
+        let http = Http::new(Config {
+            cache: Some(cache.clone()),
+            ..config()
+        })
+        .expect("Failed to build client");
+        let first = http.get_text(&server.uri()).await.expect("Request failed");
+        let second = http.get_text(&server.uri()).await.expect("Request failed");
+        let offline = Http::new(Config {
+            cache: Some(cache.clone()),
+            offline: true,
+            ..config()
+        })
+        .expect("Failed to build client");
+
+        assert_eq!((first.as_str(), second.as_str()), ("fresh", "fresh"));
+        assert_eq!(
+            offline.get_text(&server.uri()).await.expect("Cached"),
+            "fresh"
+        );
+        assert!(matches!(
+            offline.get_text("https://example.com").await,
+            Err(Error::Offline(_))
+        ));
+        let _ = std::fs::remove_dir_all(&cache.dir);
+    }
+
+    #[tokio::test]
+    async fn test_fresh_bypasses_cache() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("fresh"))
+            .expect(2)
+            .mount(&server)
+            .await;
+        let cache = Cache {
+            dir: std::env::temp_dir().join(format!("watchlist-{}-http-fresh", std::process::id())),
+            ttl: Duration::from_secs(60),
+        };
+
+        let http = Http::new(Config {
+            cache: Some(cache.clone()),
+            ..config()
+        })
+        .expect("Failed to build client");
+        http.get_text(&server.uri()).await.expect("Request failed");
+        fresh(http.get_text(&server.uri()))
+            .await
+            .expect("Request failed");
+
+        assert_eq!(cache.get(&server.uri()).as_deref(), Some("fresh"));
+        let _ = std::fs::remove_dir_all(&cache.dir);
+    }
+
+    #[tokio::test]
+    async fn test_spaces_requests_to_the_same_host() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&server)
+            .await;
+
+        let http = Http::new(Config {
+            min_interval: Duration::from_millis(100),
+            ..config()
+        })
+        .expect("Failed to build client");
+        let start = Instant::now();
+        for _ in 0..3 {
+            http.get_text(&server.uri()).await.expect("Request failed");
+        }
+
+        assert!(start.elapsed() >= Duration::from_millis(200));
+    }
+}
@@ -0,0 +1,28 @@
+//! Builds TradingView watchlists from exchange, index and fund data.
+//!
+//! Sources live in [`exchanges`] and return watchlist symbols such as
+//! `BINANCE:BTCUSDT`, usually through a pure `process_*` or `parse_*`
+//! function that can be fed a saved response. [`filters`], [`combine`] and
+//! [`symbols`] narrow and merge those lists, and [`output`] and [`utils`]
+//! render and write them. Network access goes through [`http`], which is
+//! configured once with [`http::init`]. [`commands`] ties these together
+//! into the commands the `watchlist` binary runs, once, on a schedule or
+//! over HTTP.
+
+pub mod cache;
+pub mod calendar;
+pub mod combine;
+pub mod commands;
+pub mod diff;
+pub mod earnings;
+pub mod events;
+pub mod exchanges;
+pub mod filters;
+pub mod http;
+pub mod listings;
+pub mod output;
+pub mod overlap;
+pub mod schedule;
+pub mod server;
+pub mod symbols;
+pub mod utils;
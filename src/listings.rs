@@ -0,0 +1,188 @@
+use std::collections::BTreeMap;
+use std::io;
+use std::path::Path;
+
+use chrono::{Days, NaiveDate};
+use serde_derive::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::events::Event;
+use crate::exchanges::{binance, coinbase, kucoin, woo};
+use crate::utils;
+
+/// The symbols a venue has listed, with the date each was first seen.
+///
+/// Symbols present when tracking began have no date since their listing date
+/// is unknown. Delisted symbols are kept so a relisting isn't reported as new.
+#[derive(Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub first_seen: BTreeMap<String, Option<NaiveDate>>,
+}
+
+impl Snapshot {
+    /// The snapshot saved at `path`, if there is one.
+    ///
+    /// # Errors
+    ///
+    /// When the file can't be read or isn't a snapshot.
+    pub fn load(path: &Path) -> io::Result<Option<Self>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        let data = std::fs::read_to_string(path)?;
+        serde_json::from_str(&data)
+            .map(Some)
+            .map_err(io::Error::other)
+    }
+
+    /// Writes the snapshot to `path` as JSON.
+    ///
+    /// # Errors
+    ///
+    /// When the file can't be written.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let json = serde_json::to_string_pretty(self).map_err(io::Error::other)?;
+        utils::write_file(&[json], path)
+    }
+
+    /// Records `symbols` seen on `today`. Without a previous snapshot they
+    /// become the undated baseline.
+    #[must_use]
+    pub fn update(previous: Option<Self>, symbols: &[String], today: NaiveDate) -> Self {
+        let is_baseline = previous.is_none();
+        let mut snapshot = previous.unwrap_or_default();
+        for symbol in symbols {
+            snapshot
+                .first_seen
+                .entry(symbol.clone())
+                .or_insert_with(|| (!is_baseline).then_some(today));
+        }
+        snapshot
+    }
+
+    /// Symbols first seen on or after `cutoff`.
+    #[must_use]
+    pub fn since(&self, cutoff: NaiveDate) -> Vec<Event> {
+        self.first_seen
+            .iter()
+            .filter_map(|(symbol, date)| {
+                date.filter(|d| *d >= cutoff).map(|date| Event {
+                    date,
+                    symbol: symbol.clone(),
+                })
+            })
+            .collect()
+    }
+}
+
+/// Every symbol listed on each venue tracked for new listings: Binance,
+/// KuCoin, WOO (spot and perps) and Coinbase.
+pub async fn get_venues() -> Vec<(&'static str, Vec<String>)> {
+    let mut woo = woo::get_spot().await;
+    woo.extend(woo::get_perp().await);
+    vec![
+        ("binance", binance::get_spot().await),
+        ("kucoin", kucoin::get_spot().await),
+        ("woo", woo),
+        ("coinbase", coinbase::get_spot().await),
+    ]
+}
+
+/// Records what each venue lists `today` in its snapshot under `dir` and
+/// returns the symbols first seen within the last `days` days.
+///
+/// # Errors
+///
+/// When a snapshot can't be read or written.
+pub async fn refresh(dir: &Path, today: NaiveDate, days: u32) -> io::Result<Vec<Event>> {
+    let cutoff = today
+        .checked_sub_days(Days::new(u64::from(days)))
+        .unwrap_or(today);
+
+    let mut new = Vec::new();
+    for (venue, symbols) in get_venues().await {
+        // A failed fetch returns nothing, which must not reset the snapshot.
+        if symbols.is_empty() {
+            warn!(venue, "No symbols fetched, skipping");
+            continue;
+        }
+        let path = dir.join(format!("{venue}.json"));
+        let snapshot = Snapshot::update(Snapshot::load(&path)?, &symbols, today);
+        snapshot.save(&path)?;
+        new.extend(snapshot.since(cutoff));
+    }
+    Ok(new)
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 11, day).expect("Invalid date")
+    }
+
+    fn symbols(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| (*s).to_string()).collect()
+    }
+
+    #[test]
+    fn test_first_snapshot_is_baseline() {
+        let snapshot = Snapshot::update(None, &symbols(&["BINANCE:BTCUSDT"]), date(1));
+
+        assert_eq!(snapshot.first_seen.get("BINANCE:BTCUSDT"), Some(&None));
+        assert!(snapshot.since(date(1)).is_empty());
+    }
+
+    #[test]
+    fn test_update_records_first_seen_date() {
+        let baseline = Snapshot::update(None, &symbols(&["BINANCE:BTCUSDT"]), date(1));
+        let second = Snapshot::update(
+            Some(baseline),
+            &symbols(&["BINANCE:BTCUSDT", "BINANCE:NEWUSDT"]),
+            date(3),
+        );
+        // Delisted in between and listed again: the first date is kept.
+        let third = Snapshot::update(
+            Some(second),
+            &symbols(&["BINANCE:NEWUSDT", "BINANCE:LATERUSDT"]),
+            date(10),
+        );
+
+        assert_eq!(
+            third.since(date(2)),
+            vec![
+                Event {
+                    date: date(10),
+                    symbol: "BINANCE:LATERUSDT".to_string(),
+                },
+                Event {
+                    date: date(3),
+                    symbol: "BINANCE:NEWUSDT".to_string(),
+                },
+            ]
+        );
+        assert_eq!(third.since(date(5)).len(), 1);
+        assert!(third.first_seen.contains_key("BINANCE:BTCUSDT"));
+    }
+
+    #[test]
+    fn test_save_and_load_round_trip() {
+        let dir = std::env::temp_dir().join(format!("watchlist-{}-snapshot", std::process::id()));
+        let path = dir.join("binance.json");
+        let snapshot = Snapshot::update(
+            Some(Snapshot::default()),
+            &symbols(&["BINANCE:BTCUSDT"]),
+            date(1),
+        );
+
+        snapshot.save(&path).expect("Failed to save snapshot");
+
+        assert_eq!(
+            Snapshot::load(&path).expect("Failed to load snapshot"),
+            Some(snapshot)
+        );
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
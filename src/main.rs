@@ -1,151 +1,175 @@
-use clap::{Parser, Subcommand};
-use color_eyre::eyre::Result;
-
-mod exchanges;
-mod utils;
-
-use exchanges::{binance, coingecko, earningshub, ibkr, kucoin, stockanalysis, woo};
+use chrono::Local;
+use clap::error::ErrorKind;
+use clap::{CommandFactory, Parser, ValueEnum};
+use color_eyre::eyre::{eyre, Result};
+use std::path::PathBuf;
+use std::time::Duration;
+use tracing::level_filters::LevelFilter;
+use tracing_subscriber::fmt::format::FmtSpan;
+use tracing_subscriber::EnvFilter;
+
+use watchlist::commands::{self, Commands};
+use watchlist::output::{self, Output};
+use watchlist::{cache, http};
 
 #[derive(Parser)]
 #[command(name="Watchlist", version, about, long_about = None)]
 struct Cli {
     #[command(subcommand)]
     command: Commands,
+    /// Output formats to write, comma separated
+    #[arg(
+        long,
+        global = true,
+        value_enum,
+        value_delimiter = ',',
+        default_values_t = [output::Format::Tv]
+    )]
+    format: Vec<output::Format>,
+    /// Directory watchlists are written to, created if missing
+    #[arg(long, global = true, default_value = "dist")]
+    out_dir: PathBuf,
+    /// File name template. `{market}` is the list kind (C, E, I, S,
+    /// Positions, Earnings, ...), `{source}` the exchange, fund or period and
+    /// `{date}` today's date. May contain `/` to write into subdirectories.
+    #[arg(long, global = true, default_value = "- {market} - {source}")]
+    name_template: String,
+    /// Previous versions of each watchlist to keep under `history/`
+    #[arg(long, global = true, default_value_t = 10)]
+    history: usize,
+    /// User-Agent sent with every request
+    #[arg(long, global = true, default_value = http::DEFAULT_USER_AGENT)]
+    user_agent: String,
+    /// Proxy for all requests, e.g. `http://localhost:8080` or
+    /// `socks5://localhost:1080`
+    #[arg(long, global = true)]
+    proxy: Option<String>,
+    /// Seconds before a request times out
+    #[arg(long, global = true, default_value_t = 30)]
+    timeout: u64,
+    /// Seconds to wait for a connection to be established
+    #[arg(long, global = true, default_value_t = 10)]
+    connect_timeout: u64,
+    /// Retries after a 429, a 5xx, a timeout or a connection error
+    #[arg(long, global = true, default_value_t = 3)]
+    retries: u32,
+    /// Minimum milliseconds between two requests to the same host
+    #[arg(long, global = true, default_value_t = 250)]
+    rate_limit_ms: u64,
+    /// Directory API responses are cached in
+    #[arg(long, global = true, default_value = ".cache")]
+    cache_dir: PathBuf,
+    /// Seconds a cached response is reused before fetching again, 0 to
+    /// disable the cache
+    #[arg(long, global = true, default_value_t = 300)]
+    cache_ttl: u64,
+    /// Never fetch: use cached responses regardless of age, falling back to
+    /// the fixtures in `--fixtures-dir`
+    #[arg(long, global = true)]
+    offline: bool,
+    #[arg(long, global = true, default_value = "tests/fixtures")]
+    fixtures_dir: PathBuf,
+    /// More detailed logs, -vv for everything
+    #[arg(short, long, global = true, action = clap::ArgAction::Count, conflicts_with = "quiet")]
+    verbose: u8,
+    /// Only warnings and errors, -qq for errors only
+    #[arg(short, long, global = true, action = clap::ArgAction::Count)]
+    quiet: u8,
+    #[arg(long, global = true, value_enum, default_value_t = LogFormat::Text)]
+    log_format: LogFormat,
+    /// Print symbols added and removed since the previous run
+    #[arg(long, global = true)]
+    show_diff: bool,
+    /// Also write symbols added since the previous run as a separate
+    /// `New listings` watchlist
+    #[arg(long, global = true)]
+    new_listings: bool,
 }
 
-#[derive(Subcommand)]
-enum Commands {
-    #[command(arg_required_else_help = true)]
-    #[group(required = true, multiple = true)]
-    Woo {
-        #[arg(long, requires_if("perp", "perp"))]
-        perp: bool,
-        #[arg(long, requires_if("perp", "perp"))]
-        spot: bool,
-    },
-    Binance,
-    Kucoin,
-    Coingecko,
-    Ibkr,
-    Components {
-        etf: String,
-    },
-    #[command(subcommand)]
-    Earnings(EarningsCommands),
-}
-
-#[derive(Subcommand)]
-enum EarningsCommands {
-    ThisWeek,
-    NextWeek,
-    TwoWeeks,
-}
-
-fn get_crypto_file_name(name: &str) -> String {
-    format!("- C - {name}")
+#[derive(Clone, Copy, ValueEnum)]
+enum LogFormat {
+    Text,
+    /// One JSON object per line, for log collectors
+    Json,
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
     color_eyre::install()?;
     let cli = Cli::parse();
+    if let Err(e) = cli.command.validate() {
+        Cli::command().error(ErrorKind::ValueValidation, e).exit();
+    }
+    init_logging(&cli)?;
+    http::init(http::Config {
+        user_agent: cli.user_agent.clone(),
+        proxy: cli.proxy.clone(),
+        connect_timeout: Duration::from_secs(cli.connect_timeout),
+        timeout: Duration::from_secs(cli.timeout),
+        retries: cli.retries,
+        min_interval: Duration::from_millis(cli.rate_limit_ms),
+        cache: (cli.cache_ttl > 0 || cli.offline).then(|| cache::Cache {
+            dir: cli.cache_dir.clone(),
+            ttl: Duration::from_secs(cli.cache_ttl),
+        }),
+        offline: cli.offline,
+        fixtures: cli.fixtures_dir.clone(),
+        ..http::Config::default()
+    })?;
+    if let Commands::Watch {
+        schedule,
+        now,
+        jobs,
+    } = &cli.command
+    {
+        return commands::watch(schedule, *now, jobs, || new_output(&cli)).await;
+    }
+    if let Commands::Serve { addr, jobs } = &cli.command {
+        return commands::serve(*addr, &cli.out_dir, jobs, || new_output(&cli)).await;
+    }
+    commands::run(&cli.command, &new_output(&cli)).await
+}
 
-    match &cli.command {
-        Commands::Woo { perp, spot } => {
-            if *perp {
-                let tickers = woo::get_perp().await;
-                let name = get_crypto_file_name("WOO-PERP");
-
-                //println!("{tickers:#?}\n");
-                utils::handle_file(&tickers, &name);
-            }
-            if *spot {
-                let tickers = woo::get_spot().await;
-                let name = get_crypto_file_name("WOO-SPOT");
-
-                //println!("{tickers:#?}\n");
-                utils::handle_file(&tickers, &name);
-            }
-        }
-        Commands::Binance => {
-            let tickers = binance::get_spot().await;
-            let name = get_crypto_file_name("BINANCE-SPOT");
-
-            //println!("{tickers:#?}\n");
-            utils::handle_file(&tickers, &name);
-        }
-        Commands::Kucoin => {
-            let tickers = kucoin::get_spot().await;
-            let name = get_crypto_file_name("KUCOIN-SPOT");
-
-            //println!("{tickers:#?}\n");
-            utils::handle_file(&tickers, &name);
-        }
-        Commands::Coingecko => {
-            let tickers = coingecko::get_top_100().await;
-            let name = get_crypto_file_name("COINGECKO-TOP100");
-
-            //println!("{tickers:#?}\n");
-            utils::handle_file(&tickers, &name);
-        }
-        Commands::Ibkr => {
-            let tickers = ibkr::get_tickers().await;
-
-            //println!("{tickers:#?}\n");
-            utils::handle_file(&tickers.stocks, "- Positions - Stocks");
-            utils::handle_file(&tickers.options, "- Positions - Options");
-        }
-        Commands::Components { etf } => {
-            let tickers = stockanalysis::get_components(etf).await?;
-
-            //println!("{tickers:#?}\n");
-            let etf = etf.to_uppercase();
-            let file_name = format!("- E - {etf}");
-            utils::handle_file(&tickers, &file_name);
-        }
-        Commands::Earnings(earnings_cmd) => {
-            use chrono::{Datelike, Duration, Local};
-
-            let today = Local::now().date_naive();
-
-            let monday = match earnings_cmd {
-                EarningsCommands::ThisWeek => {
-                    let days_since_monday = today.weekday().num_days_from_monday();
-                    today
-                        .checked_sub_signed(Duration::days(i64::from(days_since_monday)))
-                        .unwrap_or(today)
-                }
-                EarningsCommands::NextWeek => {
-                    let days_since_monday = today.weekday().num_days_from_monday();
-                    let this_monday = today
-                        .checked_sub_signed(Duration::days(i64::from(days_since_monday)))
-                        .unwrap_or(today);
-                    this_monday
-                        .checked_add_signed(Duration::days(7))
-                        .unwrap_or(this_monday)
-                }
-                EarningsCommands::TwoWeeks => {
-                    let days_since_monday = today.weekday().num_days_from_monday();
-                    let this_monday = today
-                        .checked_sub_signed(Duration::days(i64::from(days_since_monday)))
-                        .unwrap_or(today);
-                    this_monday
-                        .checked_add_signed(Duration::days(14))
-                        .unwrap_or(this_monday)
-                }
-            };
-
-            let week_date = monday.format("%Y-%m-%d").to_string();
-            let tickers = earningshub::get_earnings_week(&week_date).await;
-
-            let file_name = match earnings_cmd {
-                EarningsCommands::ThisWeek => "- Earnings - This Week",
-                EarningsCommands::NextWeek => "- Earnings - Next Week",
-                EarningsCommands::TwoWeeks => "- Earnings - Two Weeks",
-            };
+/// Logs to stderr at the level set by `-v`/`-q`, unless `RUST_LOG` is set.
+/// Each source runs in a span whose duration is logged when it ends.
+fn init_logging(cli: &Cli) -> Result<()> {
+    let level = match (cli.quiet, cli.verbose) {
+        (0, 0) => LevelFilter::INFO,
+        (0, 1) => LevelFilter::DEBUG,
+        (0, _) => LevelFilter::TRACE,
+        (1, _) => LevelFilter::WARN,
+        _ => LevelFilter::ERROR,
+    };
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| {
+        // Dependencies stay at warnings so -v doesn't dump HTTP internals.
+        EnvFilter::new(format!(
+            "{},watchlist={level}",
+            level.min(LevelFilter::WARN)
+        ))
+    });
+
+    let logs = tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(std::io::stderr)
+        .with_span_events(FmtSpan::CLOSE);
+    let installed = match cli.log_format {
+        LogFormat::Text => logs.with_target(false).try_init(),
+        LogFormat::Json => logs.json().try_init(),
+    };
+    installed.map_err(|e| eyre!(e))
+}
 
-            utils::handle_file(&tickers, file_name);
-        }
+/// Output settings for a run starting now.
+fn new_output(cli: &Cli) -> Output {
+    let now = Local::now();
+    Output {
+        formats: cli.format.clone(),
+        dir: cli.out_dir.clone(),
+        template: cli.name_template.clone(),
+        date: now.date_naive(),
+        history: cli.history,
+        timestamp: now.format("%Y%m%dT%H%M%S").to_string(),
+        show_diff: cli.show_diff,
+        new_listings: cli.new_listings,
     }
-    Ok(())
 }
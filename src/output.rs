@@ -0,0 +1,463 @@
+use std::path::{Path, PathBuf};
+
+use chrono::NaiveDate;
+use serde_derive::Serialize;
+
+use crate::symbols::{Market, Symbol};
+use crate::utils;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Format {
+    /// TradingView watchlist, `EXCHANGE:SYMBOL` per line with `###` sections
+    Tv,
+    /// One row per symbol with exchange, base, quote, market, section, rank
+    /// and volume
+    Csv,
+    /// Array of objects with the same fields as the CSV
+    Json,
+    /// Bare symbols, one per line, without exchange prefixes or sections
+    Plain,
+}
+
+impl Format {
+    #[must_use]
+    pub const fn extension(self) -> &'static str {
+        match self {
+            Self::Tv => "txt",
+            Self::Csv => "csv",
+            Self::Json => "json",
+            Self::Plain => "plain.txt",
+        }
+    }
+
+    /// The format a file was written in, from its extension.
+    #[must_use]
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let name = path.file_name()?.to_str()?;
+        [Self::Plain, Self::Tv, Self::Csv, Self::Json]
+            .into_iter()
+            .find(|format| name.ends_with(&format!(".{}", format.extension())))
+    }
+}
+
+/// How and where watchlists are written.
+pub struct Output {
+    pub formats: Vec<Format>,
+    pub dir: PathBuf,
+    /// File name template with `{market}`, `{source}` and `{date}`
+    /// placeholders.
+    pub template: String,
+    pub date: NaiveDate,
+    /// Previous versions to keep under `{dir}/history/{name}/`, 0 to disable.
+    pub history: usize,
+    /// Run timestamp used to name history copies.
+    pub timestamp: String,
+    /// Print symbols added and removed since the previous run.
+    pub show_diff: bool,
+    /// Also write the added symbols as a `{name} - New listings` watchlist.
+    pub new_listings: bool,
+}
+
+impl Output {
+    /// The file name, without extension, of the `source` watchlist in
+    /// `market` (e.g. `C` for crypto or `E` for ETF components).
+    #[must_use]
+    pub fn name(&self, market: &str, source: &str) -> String {
+        let date = self.date.format("%Y-%m-%d").to_string();
+        fill(
+            &self.template,
+            &[
+                ("market", &path_safe(market)),
+                ("source", &path_safe(source)),
+                ("date", &date),
+            ],
+        )
+    }
+
+    #[must_use]
+    pub fn path(&self, name: &str, extension: &str) -> PathBuf {
+        self.dir.join(format!("{name}.{extension}"))
+    }
+
+    #[must_use]
+    pub fn history_dir(&self, name: &str) -> PathBuf {
+        self.dir.join("history").join(name)
+    }
+}
+
+/// `value` as a single file name component, so values taken from arguments
+/// can't point outside the output directory. Only the template adds `/`.
+fn path_safe(value: &str) -> String {
+    let value = value.replace(['/', '\\'], "-");
+    if value.chars().all(|c| c == '.') {
+        value.replace('.', "-")
+    } else {
+        value
+    }
+}
+
+/// Replaces `{key}` placeholders in `template`. Unknown placeholders are
+/// left as they are.
+fn fill(template: &str, values: &[(&str, &str)]) -> String {
+    let mut filled = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        let (before, placeholder) = rest.split_at(start);
+        filled.push_str(before);
+
+        let value = placeholder.find('}').and_then(|end| {
+            let key = placeholder.get(1..end)?;
+            let (_, value) = values.iter().find(|(k, _)| *k == key)?;
+            Some((*value, end.saturating_add(1)))
+        });
+        let consumed = if let Some((value, consumed)) = value {
+            filled.push_str(value);
+            consumed
+        } else {
+            filled.push('{');
+            1
+        };
+        rest = placeholder.get(consumed..).unwrap_or_default();
+    }
+    filled.push_str(rest);
+    filled
+}
+
+/// A single watchlist symbol with whatever metadata its source provides.
+/// `rank` is the 1-based position in the list, which follows each source's
+/// own ordering (market cap, volume, index weight, ...).
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Entry {
+    pub ticker: String,
+    pub symbol: String,
+    pub exchange: Option<String>,
+    pub base: String,
+    pub quote: Option<String>,
+    pub market: Market,
+    pub section: Option<String>,
+    pub rank: usize,
+    pub volume: Option<f64>,
+}
+
+impl Entry {
+    /// Parses watchlist lines, attaching each symbol to the `###` section
+    /// above it.
+    #[must_use]
+    pub fn from_lines(lines: &[String]) -> Vec<Self> {
+        let mut entries = Vec::new();
+        let mut section = None;
+        for line in lines {
+            if let Some(name) = line.strip_prefix("###") {
+                section = Some(name.to_string());
+                continue;
+            }
+            let parsed = Symbol::parse(line);
+            entries.push(Self {
+                ticker: line.clone(),
+                symbol: line
+                    .split_once(':')
+                    .map_or_else(|| line.clone(), |(_, symbol)| symbol.to_string()),
+                exchange: parsed.exchange,
+                base: parsed.base,
+                quote: parsed.quote,
+                market: parsed.market,
+                section: section.clone(),
+                rank: entries.len().saturating_add(1),
+                volume: None,
+            });
+        }
+        entries
+    }
+}
+
+impl Entry {
+    /// What identifies the entry in a file of `format`: the bare symbol in
+    /// plain files, the exchange-prefixed ticker in the others.
+    #[must_use]
+    pub fn key(&self, format: Format) -> &str {
+        match format {
+            Format::Plain => &self.symbol,
+            Format::Tv | Format::Csv | Format::Json => &self.ticker,
+        }
+    }
+}
+
+/// The keys of the entries in a file written in `format`, in file order.
+pub fn keys(lines: &[String], format: Format) -> Vec<String> {
+    match format {
+        Format::Tv => Entry::from_lines(lines)
+            .into_iter()
+            .map(|e| e.ticker)
+            .collect(),
+        Format::Plain => lines.to_vec(),
+        // Tickers never need quoting, so the first field is the ticker.
+        Format::Csv => lines
+            .iter()
+            .skip(1)
+            .filter_map(|row| row.split(',').next())
+            .map(str::to_string)
+            .collect(),
+        Format::Json => serde_json::from_str::<Vec<serde_json::Value>>(&lines.join("\n"))
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|entry| entry.get("ticker")?.as_str().map(str::to_string))
+            .collect(),
+    }
+}
+
+/// Joins named groups into one watchlist, each group under its own
+/// `###` section. Ranks run across the whole list.
+#[must_use]
+pub fn sectioned(sections: Vec<(&str, Vec<Entry>)>) -> Vec<Entry> {
+    sections
+        .into_iter()
+        .flat_map(|(name, entries)| {
+            entries.into_iter().map(move |entry| Entry {
+                section: Some(name.to_string()),
+                ..entry
+            })
+        })
+        .enumerate()
+        .map(|(i, entry)| Entry {
+            rank: i.saturating_add(1),
+            ..entry
+        })
+        .collect()
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn to_tv(entries: &[Entry]) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut current = None;
+    for entry in entries {
+        if entry.section.is_some() && current != Some(&entry.section) {
+            lines.push(utils::section_header(
+                entry.section.as_deref().unwrap_or_default(),
+            ));
+            current = Some(&entry.section);
+        }
+        lines.push(entry.ticker.clone());
+    }
+    lines
+}
+
+fn to_csv(entries: &[Entry]) -> Vec<String> {
+    let header =
+        std::iter::once("ticker,symbol,exchange,base,quote,market,section,rank,volume".to_string());
+    let rows = entries.iter().map(|e| {
+        format!(
+            "{},{},{},{},{},{},{},{},{}",
+            csv_field(&e.ticker),
+            csv_field(&e.symbol),
+            csv_field(e.exchange.as_deref().unwrap_or_default()),
+            csv_field(&e.base),
+            csv_field(e.quote.as_deref().unwrap_or_default()),
+            e.market.as_str(),
+            csv_field(e.section.as_deref().unwrap_or_default()),
+            e.rank,
+            e.volume.map(|v| v.to_string()).unwrap_or_default()
+        )
+    });
+    header.chain(rows).collect()
+}
+
+/// Renders `entries` as the lines of a file in `format`.
+#[must_use]
+pub fn render(entries: &[Entry], format: Format) -> Vec<String> {
+    match format {
+        Format::Tv => to_tv(entries),
+        Format::Csv => to_csv(entries),
+        Format::Json => serde_json::to_string_pretty(entries)
+            .map(|json| vec![json])
+            .unwrap_or_default(),
+        Format::Plain => entries.iter().map(|e| e.symbol.clone()).collect(),
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    fn lines() -> Vec<String> {
+        [
+            "###Monday AMC",
+            "NYSE:WM",
+            "###Tuesday BMO",
+            "NASDAQ:SOFI",
+            "AAPL",
+        ]
+        .iter()
+        .map(|s| (*s).to_string())
+        .collect()
+    }
+
+    fn output(template: &str) -> Output {
+        Output {
+            formats: vec![Format::Tv],
+            dir: PathBuf::from("out"),
+            template: template.to_string(),
+            date: NaiveDate::from_ymd_opt(2025, 10, 30).expect("Invalid date"),
+            history: 0,
+            timestamp: "20251030T120000".to_string(),
+            show_diff: false,
+            new_listings: false,
+        }
+    }
+
+    #[test]
+    fn test_name_default_template() {
+        let output = output("- {market} - {source}");
+
+        assert_eq!(output.name("C", "BINANCE-SPOT"), "- C - BINANCE-SPOT");
+        assert_eq!(
+            output.path(&output.name("E", "SPY"), Format::Csv.extension()),
+            PathBuf::from("out/- E - SPY.csv")
+        );
+    }
+
+    #[test]
+    fn test_name_keeps_values_in_one_component() {
+        let output = output("{market}/{source}");
+
+        assert_eq!(output.name("C", "../../../x"), "C/..-..-..-x");
+        assert_eq!(output.name("..", "a\\b"), "--/a-b");
+    }
+
+    #[test]
+    fn test_fill_leaves_unknown_placeholders() {
+        assert_eq!(
+            fill("{a}-{b}-{", &[("a", "x"), ("c", "z")]),
+            "x-{b}-{".to_string()
+        );
+    }
+
+    #[test]
+    fn test_name_template_with_date_and_directories() {
+        let output = output("{market}/{date}/{source}");
+
+        assert_eq!(
+            output.path(&output.name("Positions", "Stocks"), "txt"),
+            PathBuf::from("out/Positions/2025-10-30/Stocks.txt")
+        );
+    }
+
+    #[test]
+    fn test_from_lines_tracks_sections_and_rank() {
+        let entries = Entry::from_lines(&lines());
+        let summary: Vec<(&str, Option<&str>, Option<&str>, usize)> = entries
+            .iter()
+            .map(|e| {
+                (
+                    e.symbol.as_str(),
+                    e.exchange.as_deref(),
+                    e.section.as_deref(),
+                    e.rank,
+                )
+            })
+            .collect();
+
+        assert_eq!(
+            summary,
+            vec![
+                ("WM", Some("NYSE"), Some("Monday AMC"), 1),
+                ("SOFI", Some("NASDAQ"), Some("Tuesday BMO"), 2),
+                ("AAPL", None, Some("Tuesday BMO"), 3),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_render_tv_round_trips() {
+        let entries = Entry::from_lines(&lines());
+
+        assert_eq!(render(&entries, Format::Tv), lines());
+    }
+
+    #[test]
+    fn test_sectioned_writes_one_header_per_group() {
+        let stocks = Entry::from_lines(&["NASDAQ:AAPL".to_string(), "NYSE:JPM".to_string()]);
+        let options = Entry::from_lines(&["NASDAQ:AAPL".to_string()]);
+        let empty = Vec::new();
+
+        let entries = sectioned(vec![
+            ("Stocks", stocks),
+            ("Futures", empty),
+            ("Options", options),
+        ]);
+
+        assert_eq!(
+            render(&entries, Format::Tv),
+            vec![
+                "###Stocks",
+                "NASDAQ:AAPL",
+                "NYSE:JPM",
+                "###Options",
+                "NASDAQ:AAPL"
+            ]
+        );
+        assert_eq!(entries.last().map(|e| e.rank), Some(3));
+    }
+
+    #[test]
+    fn test_render_csv_and_plain() {
+        let mut entries = Entry::from_lines(&["KUCOIN:BTCUSDT".to_string()]);
+        if let Some(entry) = entries.first_mut() {
+            entry.volume = Some(1250.5);
+            entry.section = Some("Top, by volume".to_string());
+        }
+
+        assert_eq!(
+            render(&entries, Format::Csv),
+            vec![
+                "ticker,symbol,exchange,base,quote,market,section,rank,volume",
+                "KUCOIN:BTCUSDT,BTCUSDT,KUCOIN,BTC,USDT,spot,\"Top, by volume\",1,1250.5",
+            ]
+        );
+        assert_eq!(render(&entries, Format::Plain), vec!["BTCUSDT"]);
+    }
+
+    #[test]
+    fn test_keys_read_back_every_format() {
+        let entries = Entry::from_lines(&lines());
+
+        for format in [Format::Tv, Format::Csv, Format::Json, Format::Plain] {
+            let expected: Vec<&str> = entries.iter().map(|e| e.key(format)).collect();
+
+            assert_eq!(keys(&render(&entries, format), format), expected);
+        }
+        assert_eq!(entries.first().map(|e| e.key(Format::Plain)), Some("WM"));
+    }
+
+    #[test]
+    fn test_render_json() {
+        let entries = Entry::from_lines(&["BINANCE:ETHUSDT".to_string()]);
+        let json = render(&entries, Format::Json).join("\n");
+        let parsed: serde_json::Value = serde_json::from_str(&json).expect("Invalid JSON");
+
+        assert_eq!(
+            parsed.pointer("/0/exchange"),
+            Some(&serde_json::json!("BINANCE"))
+        );
+        assert_eq!(
+            parsed.pointer("/0/symbol"),
+            Some(&serde_json::json!("ETHUSDT"))
+        );
+        assert_eq!(parsed.pointer("/0/base"), Some(&serde_json::json!("ETH")));
+        assert_eq!(
+            parsed.pointer("/0/market"),
+            Some(&serde_json::json!("spot"))
+        );
+        assert_eq!(parsed.pointer("/0/rank"), Some(&serde_json::json!(1)));
+        assert!(parsed
+            .pointer("/0/volume")
+            .is_some_and(serde_json::Value::is_null));
+    }
+}
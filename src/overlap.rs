@@ -0,0 +1,193 @@
+use color_eyre::eyre::Result;
+
+use crate::exchanges::stockanalysis::{self, Holding};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Overlap {
+    pub symbol: String,
+    pub weights: Vec<(String, f64)>,
+}
+
+impl Overlap {
+    #[must_use]
+    pub fn combined_weight(&self) -> f64 {
+        self.weights.iter().map(|(_, weight)| weight).sum()
+    }
+
+    fn weight_in(&self, etf: &str) -> Option<f64> {
+        self.weights
+            .iter()
+            .find(|(name, _)| name == etf)
+            .map(|(_, weight)| *weight)
+    }
+}
+
+/// The holdings of each of `etfs`, keyed by the upper-cased ticker.
+///
+/// # Errors
+///
+/// When the holdings of one of the funds can't be fetched.
+pub async fn get_funds(etfs: &[String]) -> Result<Vec<(String, Vec<Holding>)>> {
+    let mut funds = Vec::new();
+    for etf in etfs {
+        let etf = etf.to_uppercase();
+        let holdings = stockanalysis::get_holdings(&etf).await?;
+        funds.push((etf, holdings));
+    }
+    Ok(funds)
+}
+
+/// Groups the holdings of several ETFs by symbol, keeping each fund's weight.
+/// Results are ordered by the number of funds holding the symbol, then by
+/// combined weight.
+#[must_use]
+pub fn compute(funds: &[(String, Vec<Holding>)]) -> Vec<Overlap> {
+    let mut overlaps: Vec<Overlap> = Vec::new();
+
+    for (etf, holdings) in funds {
+        for holding in holdings {
+            let weight = holding.weight.unwrap_or(0.0);
+            match overlaps.iter_mut().find(|o| o.symbol == holding.symbol) {
+                Some(overlap) => {
+                    if overlap.weight_in(etf).is_none() {
+                        overlap.weights.push((etf.clone(), weight));
+                    }
+                }
+                None => overlaps.push(Overlap {
+                    symbol: holding.symbol.clone(),
+                    weights: vec![(etf.clone(), weight)],
+                }),
+            }
+        }
+    }
+
+    overlaps.sort_by(|a, b| {
+        b.weights.len().cmp(&a.weights.len()).then_with(|| {
+            b.combined_weight()
+                .partial_cmp(&a.combined_weight())
+                .unwrap_or(std::cmp::Ordering::Equal)
+        })
+    });
+
+    overlaps
+}
+
+#[must_use]
+pub fn union(overlaps: &[Overlap]) -> Vec<String> {
+    let mut sorted: Vec<&Overlap> = overlaps.iter().collect();
+    sorted.sort_by(|a, b| {
+        b.combined_weight()
+            .partial_cmp(&a.combined_weight())
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+    sorted.iter().map(|o| o.symbol.clone()).collect()
+}
+
+#[must_use]
+pub fn intersection(overlaps: &[Overlap], fund_count: usize) -> Vec<String> {
+    overlaps
+        .iter()
+        .filter(|o| o.weights.len() == fund_count)
+        .map(|o| o.symbol.clone())
+        .collect()
+}
+
+/// CSV rows for holdings shared by more than one fund.
+#[must_use]
+pub fn report(overlaps: &[Overlap], etfs: &[String]) -> Vec<String> {
+    let header = std::iter::once(format!("symbol,funds,combined_weight,{}", etfs.join(",")));
+
+    let rows = overlaps.iter().filter(|o| o.weights.len() > 1).map(|o| {
+        let per_fund: Vec<String> = etfs
+            .iter()
+            .map(|etf| {
+                o.weight_in(etf)
+                    .map(|weight| format!("{weight:.2}"))
+                    .unwrap_or_default()
+            })
+            .collect();
+        format!(
+            "{},{},{:.2},{}",
+            o.symbol,
+            o.weights.len(),
+            o.combined_weight(),
+            per_fund.join(",")
+        )
+    });
+
+    header.chain(rows).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn holding(symbol: &str, weight: f64) -> Holding {
+        Holding {
+            symbol: symbol.to_string(),
+            name: String::new(),
+            weight: Some(weight),
+        }
+    }
+
+    fn funds() -> Vec<(String, Vec<Holding>)> {
+        vec![
+            (
+                "SPY".to_string(),
+                vec![
+                    holding("NVDA", 8.5),
+                    holding("AAPL", 6.9),
+                    holding("JPM", 1.5),
+                ],
+            ),
+            (
+                "QQQ".to_string(),
+                vec![
+                    holding("NVDA", 9.0),
+                    holding("AAPL", 8.0),
+                    holding("NFLX", 2.0),
+                ],
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_compute_orders_shared_first() {
+        let overlaps = compute(&funds());
+        let symbols: Vec<&str> = overlaps.iter().map(|o| o.symbol.as_str()).collect();
+
+        assert_eq!(symbols, vec!["NVDA", "AAPL", "NFLX", "JPM"]);
+        assert!(overlaps
+            .first()
+            .is_some_and(|o| (o.combined_weight() - 17.5).abs() < f64::EPSILON));
+    }
+
+    #[test]
+    fn test_union_orders_by_combined_weight() {
+        let overlaps = compute(&funds());
+
+        assert_eq!(union(&overlaps), vec!["NVDA", "AAPL", "NFLX", "JPM"]);
+    }
+
+    #[test]
+    fn test_intersection() {
+        let overlaps = compute(&funds());
+
+        assert_eq!(intersection(&overlaps, 2), vec!["NVDA", "AAPL"]);
+    }
+
+    #[test]
+    fn test_report_lists_shared_holdings() {
+        let overlaps = compute(&funds());
+        let etfs = vec!["SPY".to_string(), "QQQ".to_string()];
+
+        assert_eq!(
+            report(&overlaps, &etfs),
+            vec![
+                "symbol,funds,combined_weight,SPY,QQQ",
+                "NVDA,2,17.50,8.50,9.00",
+                "AAPL,2,14.90,6.90,8.00",
+            ]
+        );
+    }
+}
@@ -0,0 +1,254 @@
+use std::str::FromStr;
+
+use chrono::{Datelike, LocalResult, NaiveDateTime, TimeDelta, Timelike};
+
+/// Allowed values of one cron field, as a bit per value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Field {
+    bits: u64,
+    /// `*` or `*/n`, which matters for how days of month and week combine
+    any: bool,
+}
+
+impl Field {
+    fn parse(field: &str, min: u32, max: u32) -> Result<Self, String> {
+        let mut bits = 0_u64;
+        for item in field.split(',') {
+            let (range, step) = match item.split_once('/') {
+                Some((range, step)) => {
+                    let step: u32 = step
+                        .parse()
+                        .map_err(|_| format!("invalid step in {item:?}"))?;
+                    if step == 0 {
+                        return Err(format!("zero step in {item:?}"));
+                    }
+                    (range, step)
+                }
+                None => (item, 1),
+            };
+            let number = |s: &str| -> Result<u32, String> {
+                s.parse::<u32>()
+                    .ok()
+                    .filter(|n| (min..=max).contains(n))
+                    .ok_or_else(|| format!("{s:?} is not in {min}-{max}"))
+            };
+            let (from, to) = match range {
+                "*" => (min, max),
+                _ => match range.split_once('-') {
+                    Some((from, to)) => (number(from)?, number(to)?),
+                    None if step > 1 => (number(range)?, max),
+                    None => (number(range)?, number(range)?),
+                },
+            };
+            if from > to {
+                return Err(format!("empty range {range:?}"));
+            }
+            for value in (from..=to).step_by(step.try_into().unwrap_or(1)) {
+                bits |= 1 << value;
+            }
+        }
+        Ok(Self {
+            bits,
+            any: field.starts_with('*'),
+        })
+    }
+
+    const fn contains(self, value: u32) -> bool {
+        value < 64 && self.bits & (1 << value) != 0
+    }
+}
+
+/// A cron expression: minute, hour, day of month, month and day of week
+/// (0-7, Sunday being 0 or 7), e.g. `0 8 * * 1-5` for 08:00 on weekdays.
+///
+/// Like cron, a time matches both day fields when either is `*` and either
+/// of them otherwise.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Schedule {
+    minutes: Field,
+    hours: Field,
+    days: Field,
+    months: Field,
+    weekdays: Field,
+}
+
+impl FromStr for Schedule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let fields: Vec<&str> = s.split_whitespace().collect();
+        let [minutes, hours, days, months, weekdays] = fields.as_slice() else {
+            return Err(format!("expected 5 fields, got {}", fields.len()));
+        };
+        let mut weekdays = Field::parse(weekdays, 0, 7)?;
+        if weekdays.contains(7) {
+            weekdays.bits |= 1;
+        }
+        Ok(Self {
+            minutes: Field::parse(minutes, 0, 59)?,
+            hours: Field::parse(hours, 0, 23)?,
+            days: Field::parse(days, 1, 31)?,
+            months: Field::parse(months, 1, 12)?,
+            weekdays,
+        })
+    }
+}
+
+impl Schedule {
+    fn matches_day(&self, time: NaiveDateTime) -> bool {
+        let day = self.days.contains(time.day());
+        let weekday = self
+            .weekdays
+            .contains(time.weekday().num_days_from_sunday());
+        let day_matches = if self.days.any || self.weekdays.any {
+            day && weekday
+        } else {
+            day || weekday
+        };
+        self.months.contains(time.month()) && day_matches
+    }
+
+    /// The first matching minute strictly after `after`, or `None` when
+    /// nothing matches within the next five years, e.g. for `0 0 31 2 *`.
+    #[must_use]
+    pub fn next_after(&self, after: NaiveDateTime) -> Option<NaiveDateTime> {
+        let mut time = after
+            .with_second(0)?
+            .with_nanosecond(0)?
+            .checked_add_signed(TimeDelta::minutes(1))?;
+        let limit = after.checked_add_signed(TimeDelta::days(5 * 366))?;
+
+        while time < limit {
+            if !self.matches_day(time) {
+                time = time.date().succ_opt()?.and_hms_opt(0, 0, 0)?;
+            } else if !self.hours.contains(time.hour()) {
+                time = time
+                    .with_minute(0)?
+                    .checked_add_signed(TimeDelta::hours(1))?;
+            } else if !self.minutes.contains(time.minute()) {
+                time = time.checked_add_signed(TimeDelta::minutes(1))?;
+            } else {
+                return Some(time);
+            }
+        }
+        None
+    }
+
+    /// The next match after `after` as an instant in the time zone `local`
+    /// maps to, e.g. `|t| Local.from_local_datetime(&t)`. A match skipped by
+    /// a DST change runs at the first local time after the gap, and one that
+    /// happens twice runs the first time.
+    pub fn next_instant<T>(
+        &self,
+        after: NaiveDateTime,
+        local: impl Fn(NaiveDateTime) -> LocalResult<T>,
+    ) -> Option<T> {
+        let mut time = self.next_after(after)?;
+        // DST gaps last an hour or two, so a day is plenty.
+        for _ in 0..24 * 60 {
+            if let Some(instant) = local(time).earliest() {
+                return Some(instant);
+            }
+            time = time.checked_add_signed(TimeDelta::minutes(1))?;
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    fn at(s: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M").expect("Invalid time")
+    }
+
+    fn next(schedule: &str, after: &str) -> Option<NaiveDateTime> {
+        schedule
+            .parse::<Schedule>()
+            .expect("Invalid schedule")
+            .next_after(at(after))
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!("0 8 * *".parse::<Schedule>().is_err());
+        assert!("60 8 * * *".parse::<Schedule>().is_err());
+        assert!("0 8 * * 1-8".parse::<Schedule>().is_err());
+        assert!("*/0 * * * *".parse::<Schedule>().is_err());
+        assert!("0 9-8 * * *".parse::<Schedule>().is_err());
+    }
+
+    #[test]
+    fn test_next_daily() {
+        assert_eq!(
+            next("0 8 * * *", "2026-10-19 07:30"),
+            Some(at("2026-10-19 08:00"))
+        );
+        assert_eq!(
+            next("0 8 * * *", "2026-10-19 08:00"),
+            Some(at("2026-10-20 08:00"))
+        );
+    }
+
+    #[test]
+    fn test_next_weekday_and_steps() {
+        // 2026-10-23 is a Friday.
+        assert_eq!(
+            next("30 8 * * 1-5", "2026-10-23 09:00"),
+            Some(at("2026-10-26 08:30"))
+        );
+        assert_eq!(
+            next("*/15 * * * *", "2026-10-19 10:07"),
+            Some(at("2026-10-19 10:15"))
+        );
+        assert_eq!(
+            next("0 0 * * 7", "2026-10-19 10:00"),
+            Some(at("2026-10-25 00:00"))
+        );
+    }
+
+    #[test]
+    fn test_day_of_month_or_weekday() {
+        // The 1st of the month or any Monday, whichever comes first.
+        assert_eq!(
+            next("0 6 1 * 1", "2026-10-27 00:00"),
+            Some(at("2026-11-01 06:00"))
+        );
+        assert_eq!(
+            next("0 6 1 * 1", "2026-10-20 00:00"),
+            Some(at("2026-10-26 06:00"))
+        );
+        assert_eq!(next("0 0 31 2 *", "2026-10-19 00:00"), None);
+    }
+
+    #[test]
+    fn test_next_instant_across_dst_changes() {
+        // Clocks skip 02:00-03:00 on 2026-03-29 and repeat 02:00-03:00 on
+        // 2026-10-25, as in most of Europe.
+        let local = |time: NaiveDateTime| {
+            if time >= at("2026-03-29 02:00") && time < at("2026-03-29 03:00") {
+                LocalResult::None
+            } else if time >= at("2026-10-25 02:00") && time < at("2026-10-25 03:00") {
+                LocalResult::Ambiguous((time, 1), (time, 2))
+            } else {
+                LocalResult::Single((time, 1))
+            }
+        };
+        let schedule: Schedule = "30 2 * * *".parse().expect("Invalid schedule");
+
+        assert_eq!(
+            schedule.next_instant(at("2026-03-29 01:00"), local),
+            Some((at("2026-03-29 03:00"), 1))
+        );
+        assert_eq!(
+            schedule.next_instant(at("2026-03-29 03:00"), local),
+            Some((at("2026-03-30 02:30"), 1))
+        );
+        assert_eq!(
+            schedule.next_instant(at("2026-10-25 01:00"), local),
+            Some((at("2026-10-25 02:30"), 1))
+        );
+    }
+}
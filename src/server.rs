@@ -0,0 +1,384 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::net::SocketAddr;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+
+use axum::extract::{Path as UrlPath, Query, State};
+use axum::http::{header, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde_derive::{Deserialize, Serialize};
+use tokio::sync::{mpsc, oneshot};
+use tracing::error;
+
+use crate::output::{self, Entry, Format};
+use crate::utils;
+
+/// `format` query values, in the order a file's extension is checked so
+/// `.plain.txt` isn't taken for `.txt`.
+const FORMATS: [(&str, Format); 4] = [
+    ("plain", Format::Plain),
+    ("txt", Format::Tv),
+    ("csv", Format::Csv),
+    ("json", Format::Json),
+];
+
+/// Directories in the output directory that don't hold watchlists.
+const SKIPPED_DIRS: [&str; 2] = ["history", "snapshots"];
+
+/// A `POST /refresh/{name}` waiting for the job to run.
+pub struct Refresh {
+    /// One of the job names the server was started with
+    pub name: String,
+    pub done: oneshot::Sender<Outcome>,
+}
+
+#[derive(Debug)]
+pub enum Outcome {
+    Done,
+    Failed(String),
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize)]
+pub struct Listing {
+    pub name: String,
+    pub formats: Vec<&'static str>,
+}
+
+#[derive(Clone)]
+struct AppState {
+    dir: PathBuf,
+    /// Names of the jobs `POST /refresh/{name}` may run
+    jobs: Arc<[String]>,
+    refresh: mpsc::Sender<Refresh>,
+}
+
+#[derive(Deserialize)]
+struct WatchlistQuery {
+    format: Option<String>,
+}
+
+/// Splits a file name relative to the output directory into the watchlist
+/// name and its `format` query value.
+fn split_name(file: &str) -> Option<(String, &'static str)> {
+    FORMATS.iter().find_map(|(key, format)| {
+        file.strip_suffix(format.extension())?
+            .strip_suffix('.')
+            .map(|name| (name.to_string(), *key))
+    })
+}
+
+/// Watchlists in `dir`, including those written to subdirectories by a
+/// `--name-template` containing `/`.
+///
+/// # Errors
+///
+/// When `dir` or one of its subdirectories can't be read.
+pub fn list(dir: &Path) -> io::Result<Vec<Listing>> {
+    let mut found: BTreeMap<String, Vec<&'static str>> = BTreeMap::new();
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(current) = pending.pop() {
+        for entry in fs::read_dir(&current)? {
+            let path = entry?.path();
+            let Some(relative) = path.strip_prefix(dir).ok().and_then(Path::to_str) else {
+                continue;
+            };
+            if path.is_dir() {
+                if !SKIPPED_DIRS.contains(&relative) {
+                    pending.push(path.clone());
+                }
+            } else if let Some((name, format)) = split_name(relative) {
+                found.entry(name).or_default().push(format);
+            }
+        }
+    }
+
+    Ok(found
+        .into_iter()
+        .map(|(name, mut formats)| {
+            formats.sort_unstable();
+            Listing { name, formats }
+        })
+        .collect())
+}
+
+/// Only plain relative names, so requests can't read outside `dir`.
+fn is_safe(name: &str) -> bool {
+    Path::new(name)
+        .components()
+        .all(|c| matches!(c, Component::Normal(_)))
+}
+
+/// The watchlist `name` in `format`, rendered from its TradingView file when
+/// that format wasn't written.
+///
+/// # Errors
+///
+/// When the watchlist file exists but can't be read.
+pub fn read(dir: &Path, name: &str, format: Format) -> io::Result<Option<String>> {
+    let path = dir.join(format!("{name}.{}", format.extension()));
+    if path.exists() {
+        return fs::read_to_string(path).map(Some);
+    }
+    let tv = dir.join(format!("{name}.{}", Format::Tv.extension()));
+    if format == Format::Tv || !tv.exists() {
+        return Ok(None);
+    }
+
+    let entries = Entry::from_lines(&utils::read_file(&tv)?);
+    let mut body = String::new();
+    for line in output::render(&entries, format) {
+        body.push_str(&line);
+        body.push('\n');
+    }
+    Ok(Some(body))
+}
+
+const fn content_type(format: Format) -> &'static str {
+    match format {
+        Format::Tv | Format::Plain => "text/plain; charset=utf-8",
+        Format::Csv => "text/csv; charset=utf-8",
+        Format::Json => "application/json",
+    }
+}
+
+fn error(status: StatusCode, message: impl Into<String>) -> Response {
+    (status, message.into()).into_response()
+}
+
+async fn watchlists(State(state): State<AppState>) -> Response {
+    match list(&state.dir) {
+        Ok(listings) => Json(listings).into_response(),
+        Err(e) => error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    }
+}
+
+async fn watchlist(
+    State(state): State<AppState>,
+    UrlPath(name): UrlPath<String>,
+    Query(query): Query<WatchlistQuery>,
+) -> Response {
+    let key = query.format.as_deref().unwrap_or("txt");
+    let Some((_, format)) = FORMATS.iter().find(|(k, _)| *k == key) else {
+        return error(
+            StatusCode::BAD_REQUEST,
+            format!("unknown format {key:?}, expected txt, csv, json or plain"),
+        );
+    };
+    if !is_safe(&name) {
+        return error(StatusCode::BAD_REQUEST, "invalid watchlist name");
+    }
+
+    match read(&state.dir, &name, *format) {
+        Ok(Some(body)) => ([(header::CONTENT_TYPE, content_type(*format))], body).into_response(),
+        Ok(None) => error(
+            StatusCode::NOT_FOUND,
+            format!("no watchlist named {name:?}"),
+        ),
+        Err(e) => error(StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+    }
+}
+
+async fn refresh(State(state): State<AppState>, UrlPath(name): UrlPath<String>) -> Response {
+    if !state.jobs.contains(&name) {
+        return error(
+            StatusCode::NOT_FOUND,
+            format!(
+                "no job named {name:?}, expected one of: {}",
+                state.jobs.join(", ")
+            ),
+        );
+    }
+
+    let (done, outcome) = oneshot::channel();
+    let sent = state
+        .refresh
+        .send(Refresh {
+            name: name.clone(),
+            done,
+        })
+        .await;
+    if sent.is_err() {
+        return error(StatusCode::SERVICE_UNAVAILABLE, "refreshes are not running");
+    }
+
+    match outcome.await {
+        Ok(Outcome::Done) => Json(serde_json::json!({ "refreshed": name })).into_response(),
+        Ok(Outcome::Failed(e)) => error(StatusCode::INTERNAL_SERVER_ERROR, e),
+        Err(_) => error(StatusCode::SERVICE_UNAVAILABLE, "refresh was dropped"),
+    }
+}
+
+fn router(state: AppState) -> Router {
+    Router::new()
+        .route("/watchlists", get(watchlists))
+        .route("/watchlists/{*name}", get(watchlist))
+        .route("/refresh/{name}", post(refresh))
+        .with_state(state)
+}
+
+/// Serves the watchlists in `dir` on `addr` in the background. Refreshes of
+/// the named `jobs` arrive on the returned channel for the caller to run,
+/// one at a time; other names are rejected.
+///
+/// # Errors
+///
+/// When `addr` can't be bound.
+pub async fn start(
+    addr: SocketAddr,
+    dir: PathBuf,
+    jobs: Vec<String>,
+) -> io::Result<(SocketAddr, mpsc::Receiver<Refresh>)> {
+    let (refresh, requests) = mpsc::channel(16);
+    let listener = tokio::net::TcpListener::bind(addr).await?;
+    let local = listener.local_addr()?;
+
+    let app = router(AppState {
+        dir,
+        jobs: jobs.into(),
+        refresh,
+    });
+    tokio::spawn(async move {
+        if let Err(e) = axum::serve(listener, app).await {
+            error!(error = %e, "Server stopped");
+        }
+    });
+    Ok((local, requests))
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("watchlist-{}-{name}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("history").join("- C - OLD")).expect("Failed to create dir");
+        dir
+    }
+
+    #[test]
+    fn test_split_name() {
+        assert_eq!(
+            split_name("- C - WOO.plain.txt"),
+            Some(("- C - WOO".to_string(), "plain"))
+        );
+        assert_eq!(
+            split_name("crypto/- C - WOO.txt"),
+            Some(("crypto/- C - WOO".to_string(), "txt"))
+        );
+        assert_eq!(split_name("notes.md"), None);
+    }
+
+    #[test]
+    fn test_list_groups_formats_and_skips_history() {
+        let dir = temp_dir("server-list");
+        for file in ["- C - WOO.txt", "- C - WOO.json", "history/- C - OLD/1.txt"] {
+            fs::write(dir.join(file), "WOONETWORK:BTCUSDT\n").expect("Failed to write");
+        }
+
+        assert_eq!(
+            list(&dir).expect("Failed to list"),
+            vec![Listing {
+                name: "- C - WOO".to_string(),
+                formats: vec!["json", "txt"],
+            }]
+        );
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_read_renders_missing_formats() {
+        let dir = temp_dir("server-read");
+        fs::write(dir.join("- C - WOO.txt"), "###Spot\nWOONETWORK:BTCUSDT\n")
+            .expect("Failed to write");
+
+        let json = read(&dir, "- C - WOO", Format::Json)
+            .expect("Failed to read")
+            .expect("Watchlist should exist");
+        let parsed: serde_json::Value = serde_json::from_str(&json).expect("Invalid JSON");
+
+        assert_eq!(
+            parsed.pointer("/0/section").and_then(|v| v.as_str()),
+            Some("Spot")
+        );
+        assert_eq!(read(&dir, "- C - NONE", Format::Json).ok(), Some(None));
+        assert!(!is_safe("../secrets"));
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_serves_and_refreshes() {
+        let dir = temp_dir("server-http");
+        fs::write(dir.join("- C - WOO.txt"), "WOONETWORK:BTCUSDT\n").expect("Failed to write");
+        let jobs = vec!["woo".to_string(), "broken".to_string()];
+        let (addr, mut refreshes) = start(SocketAddr::from(([127, 0, 0, 1], 0)), dir.clone(), jobs)
+            .await
+            .expect("Failed to start server");
+        tokio::spawn(async move {
+            while let Some(refresh) = refreshes.recv().await {
+                let outcome = if refresh.name == "woo" {
+                    Outcome::Done
+                } else {
+                    Outcome::Failed("fetch failed".to_string())
+                };
+                let _ = refresh.done.send(outcome);
+            }
+        });
+        let client = reqwest::Client::new();
+        let base = format!("http://{addr}");
+
+        let list: serde_json::Value = client
+            .get(format!("{base}/watchlists"))
+            .send()
+            .await
+            .expect("Request failed")
+            .json()
+            .await
+            .expect("Invalid JSON");
+        let txt = client
+            .get(format!("{base}/watchlists/- C - WOO?format=txt"))
+            .send()
+            .await
+            .expect("Request failed");
+        let missing = client
+            .get(format!("{base}/watchlists/- C - NONE"))
+            .send()
+            .await
+            .expect("Request failed");
+        let refreshed = client
+            .post(format!("{base}/refresh/woo"))
+            .send()
+            .await
+            .expect("Request failed");
+        let failed = client
+            .post(format!("{base}/refresh/broken"))
+            .send()
+            .await
+            .expect("Request failed");
+        let unknown = client
+            .post(format!("{base}/refresh/combine ..%2Fsecrets"))
+            .send()
+            .await
+            .expect("Request failed");
+
+        assert_eq!(
+            list.pointer("/0/name").and_then(|v| v.as_str()),
+            Some("- C - WOO")
+        );
+        assert_eq!(txt.text().await.expect("No body"), "WOONETWORK:BTCUSDT\n");
+        assert_eq!(missing.status(), StatusCode::NOT_FOUND.as_u16());
+        assert_eq!(refreshed.status(), StatusCode::OK.as_u16());
+        assert_eq!(failed.status(), StatusCode::INTERNAL_SERVER_ERROR.as_u16());
+        assert_eq!(unknown.status(), StatusCode::NOT_FOUND.as_u16());
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
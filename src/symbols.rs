@@ -0,0 +1,259 @@
+use std::collections::HashMap;
+
+use serde_derive::Serialize;
+
+use crate::exchanges::{binance, coinbase, kucoin, woo};
+use crate::output::Entry;
+
+/// Quote currencies stripped to find a crypto pair's base asset, longest
+/// first so `FDUSD` isn't read as `FD` + `USD`.
+const QUOTES: [&str; 6] = ["FDUSD", "USDT", "USDC", "BUSD", "USD", "EUR"];
+
+/// Venues whose symbols are always crypto pairs.
+const CRYPTO_EXCHANGES: [&str; 4] = ["BINANCE", "KUCOIN", "WOONETWORK", "COINBASE"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Market {
+    Spot,
+    /// TradingView's `.P` suffix
+    Perpetual,
+    Stock,
+}
+
+impl Market {
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Spot => "spot",
+            Self::Perpetual => "perpetual",
+            Self::Stock => "stock",
+        }
+    }
+}
+
+/// A watchlist symbol split back into its parts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Symbol {
+    pub exchange: Option<String>,
+    pub base: String,
+    pub quote: Option<String>,
+    pub market: Market,
+}
+
+impl Symbol {
+    /// Parses any symbol this tool emits, e.g. `BINANCE:BTCUSDT`,
+    /// `WOONETWORK:BTCUSDT.P` or `NASDAQ:AAPL`. Unprefixed symbols are crypto
+    /// pairs when they end in a known quote and stocks otherwise.
+    #[must_use]
+    pub fn parse(ticker: &str) -> Self {
+        let (exchange, symbol) = match ticker.split_once(':') {
+            Some((exchange, symbol)) => (Some(exchange.to_string()), symbol),
+            None => (None, ticker),
+        };
+        let is_crypto_venue = exchange
+            .as_deref()
+            .is_some_and(|e| CRYPTO_EXCHANGES.contains(&e));
+        let is_stock_venue = exchange.is_some() && !is_crypto_venue;
+
+        let (pair, perpetual) = match symbol.strip_suffix(".P") {
+            Some(pair) if !is_stock_venue => (pair, true),
+            _ => (symbol, false),
+        };
+        let split = QUOTES.iter().find_map(|quote| {
+            pair.strip_suffix(quote)
+                .filter(|base| !base.is_empty())
+                .map(|base| (base, *quote))
+        });
+
+        match split {
+            Some((base, quote)) if !is_stock_venue => Self {
+                exchange,
+                base: base.to_string(),
+                quote: Some(quote.to_string()),
+                market: if perpetual {
+                    Market::Perpetual
+                } else {
+                    Market::Spot
+                },
+            },
+            _ => Self {
+                exchange,
+                base: symbol.to_string(),
+                quote: None,
+                market: Market::Stock,
+            },
+        }
+    }
+}
+
+/// The base asset of a watchlist symbol, e.g. `BTC` for `BINANCE:BTCUSDT`
+/// and `WOONETWORK:BTCUSDT.P`, or the symbol itself for stocks.
+#[must_use]
+pub fn base_asset(ticker: &str) -> String {
+    Symbol::parse(ticker).base
+}
+
+/// Merges per-venue lists, given in priority order, into one entry per base
+/// asset.
+///
+/// The highest-priority venue listing an asset wins unless `by_volume` is
+/// set, in which case the venue with the highest reported volume wins and
+/// venues without volume data only break ties by priority.
+#[must_use]
+pub fn merge(venues: &[Vec<Entry>], by_volume: bool) -> Vec<Entry> {
+    let mut order: Vec<String> = Vec::new();
+    let mut best: HashMap<String, &Entry> = HashMap::new();
+
+    for entry in venues.iter().flatten() {
+        let base = base_asset(&entry.ticker);
+        match best.get(&base) {
+            None => {
+                order.push(base.clone());
+                best.insert(base, entry);
+            }
+            Some(current) => {
+                let louder = entry
+                    .volume
+                    .is_some_and(|v| current.volume.is_none_or(|c| v > c));
+                if by_volume && louder {
+                    best.insert(base, entry);
+                }
+            }
+        }
+    }
+
+    order
+        .iter()
+        .filter_map(|base| best.get(base))
+        .enumerate()
+        .map(|(i, entry)| Entry {
+            rank: i.saturating_add(1),
+            ..(*entry).clone()
+        })
+        .collect()
+}
+
+/// Crypto venues [`all_crypto`] takes spot pairs from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Venue {
+    Binance,
+    Coinbase,
+    Kucoin,
+    Woo,
+}
+
+/// Spot pairs on `venue`, with their 24h volume where the venue reports it.
+pub async fn spot_entries(venue: Venue) -> Vec<Entry> {
+    match venue {
+        Venue::Binance => binance::get_spot_entries().await,
+        Venue::Coinbase => Entry::from_lines(&coinbase::get_spot().await),
+        Venue::Kucoin => kucoin::get_spot_entries().await,
+        Venue::Woo => Entry::from_lines(&woo::get_spot().await),
+    }
+}
+
+/// One spot pair per crypto asset across `priority` venues, see [`merge`].
+pub async fn all_crypto(priority: &[Venue], by_volume: bool) -> Vec<Entry> {
+    let mut venues = Vec::new();
+    for venue in priority {
+        venues.push(spot_entries(*venue).await);
+    }
+    merge(&venues, by_volume)
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    fn entry(ticker: &str, volume: Option<f64>) -> Entry {
+        let mut entry = Entry::from_lines(&[ticker.to_string()])
+            .pop()
+            .expect("One line should give one entry");
+        entry.volume = volume;
+        entry
+    }
+
+    #[test]
+    fn test_parse_crypto_symbols() {
+        assert_eq!(
+            Symbol::parse("BINANCE:BTCUSDT"),
+            Symbol {
+                exchange: Some("BINANCE".to_string()),
+                base: "BTC".to_string(),
+                quote: Some("USDT".to_string()),
+                market: Market::Spot,
+            }
+        );
+        assert_eq!(
+            Symbol::parse("WOONETWORK:SOLUSDT.P"),
+            Symbol {
+                exchange: Some("WOONETWORK".to_string()),
+                base: "SOL".to_string(),
+                quote: Some("USDT".to_string()),
+                market: Market::Perpetual,
+            }
+        );
+        assert_eq!(Symbol::parse("BINANCE:BTCFDUSD").base, "BTC");
+        assert_eq!(Symbol::parse("COINBASE:AVAXUSD").base, "AVAX");
+    }
+
+    #[test]
+    fn test_parse_stocks() {
+        assert_eq!(
+            Symbol::parse("NASDAQ:AAPL"),
+            Symbol {
+                exchange: Some("NASDAQ".to_string()),
+                base: "AAPL".to_string(),
+                quote: None,
+                market: Market::Stock,
+            }
+        );
+        assert_eq!(Symbol::parse("NYSE:BRK.B").market, Market::Stock);
+        assert_eq!(Symbol::parse("AAPL").market, Market::Stock);
+        assert_eq!(base_asset("USDT"), "USDT");
+    }
+
+    #[test]
+    fn test_merge_by_priority() {
+        let binance = vec![entry("BINANCE:BTCUSDT", None)];
+        let kucoin = vec![
+            entry("KUCOIN:BTCUSDT", Some(10.0)),
+            entry("KUCOIN:KCSUSDT", Some(1.0)),
+        ];
+
+        let merged: Vec<(String, usize)> = merge(&[binance, kucoin], false)
+            .into_iter()
+            .map(|e| (e.ticker, e.rank))
+            .collect();
+
+        assert_eq!(
+            merged,
+            vec![
+                ("BINANCE:BTCUSDT".to_string(), 1),
+                ("KUCOIN:KCSUSDT".to_string(), 2),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_merge_by_volume() {
+        let binance = vec![
+            entry("BINANCE:BTCUSDT", Some(5.0)),
+            entry("BINANCE:ETHUSDT", Some(50.0)),
+        ];
+        let kucoin = vec![
+            entry("KUCOIN:BTCUSDT", Some(10.0)),
+            entry("KUCOIN:ETHUSDT", Some(20.0)),
+        ];
+        let woo = vec![entry("WOONETWORK:BTCUSDT", None)];
+
+        let merged: Vec<String> = merge(&[binance, kucoin, woo], true)
+            .into_iter()
+            .map(|e| e.ticker)
+            .collect();
+
+        assert_eq!(merged, vec!["KUCOIN:BTCUSDT", "BINANCE:ETHUSDT"]);
+    }
+}
@@ -1,21 +1,332 @@
-use std::fs::File;
-use std::io::{self, BufWriter, Write};
+use std::collections::HashSet;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use tracing::{error, info, warn};
 
-pub fn write_file(lines: &[String], name: &str) -> io::Result<()> {
-    let filename = format!("dist/{name}.txt");
-    let file = File::create(filename)?;
+use crate::diff;
+use crate::output::{self, Entry, Format, Output};
+
+fn temp_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".tmp");
+    path.with_file_name(name)
+}
+
+/// Writes `lines` to a temporary file next to `path` and renames it into
+/// place, so an interrupted run never leaves a half-written watchlist.
+///
+/// # Errors
+///
+/// When the directory, the temporary file or the rename fails.
+pub fn write_file(lines: &[String], path: &Path) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let tmp = temp_path(path);
+    let file = File::create(&tmp)?;
     let mut writer = BufWriter::new(file);
 
     for line in lines {
         writeln!(writer, "{line}")?;
     }
 
-    writer.flush()
+    writer.flush()?;
+    writer.get_ref().sync_all()?;
+    fs::rename(&tmp, path).inspect_err(|_| {
+        let _ = fs::remove_file(&tmp);
+    })
+}
+
+/// Whether `path` already holds exactly `lines`, as written by `write_file`.
+#[must_use]
+pub fn unchanged(lines: &[String], path: &Path) -> bool {
+    fs::read_to_string(path).is_ok_and(|existing| {
+        let mut expected = String::with_capacity(existing.len());
+        for line in lines {
+            expected.push_str(line);
+            expected.push('\n');
+        }
+        existing == expected
+    })
+}
+
+/// History copies of one format in `history_dir`, oldest first.
+///
+/// # Errors
+///
+/// When `history_dir` can't be read.
+pub fn archived(history_dir: &Path, extension: &str) -> io::Result<Vec<PathBuf>> {
+    if !history_dir.exists() {
+        return Ok(Vec::new());
+    }
+    let suffix = format!(".{extension}");
+    let mut copies: Vec<PathBuf> = fs::read_dir(history_dir)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|copy| {
+            copy.file_name()
+                .and_then(|name| name.to_str())
+                .and_then(|name| name.strip_suffix(&suffix))
+                .is_some_and(|stem| !stem.contains('.'))
+        })
+        .collect();
+    copies.sort();
+    Ok(copies)
+}
+
+/// Copies the current `path` into `history_dir` as `{timestamp}.{extension}`
+/// before it is replaced by `lines`.
+///
+/// Only the newest `keep` copies are kept, and nothing is archived when the
+/// contents are unchanged.
+///
+/// # Errors
+///
+/// When `path` can't be read or a copy can't be written or removed.
+pub fn archive(
+    path: &Path,
+    lines: &[String],
+    history_dir: &Path,
+    timestamp: &str,
+    extension: &str,
+    keep: usize,
+) -> io::Result<()> {
+    if keep == 0 || !path.exists() {
+        return Ok(());
+    }
+    let previous = fs::read_to_string(path)?;
+    if previous.lines().eq(lines.iter().map(String::as_str)) {
+        return Ok(());
+    }
+
+    fs::create_dir_all(history_dir)?;
+    fs::copy(path, history_dir.join(format!("{timestamp}.{extension}")))?;
+
+    let copies = archived(history_dir, extension)?;
+    let excess = copies.len().saturating_sub(keep);
+    for copy in copies.iter().take(excess) {
+        fs::remove_file(copy)?;
+    }
+    Ok(())
+}
+
+/// The non-empty lines of `path`.
+///
+/// # Errors
+///
+/// When `path` can't be read.
+pub fn read_file(path: &Path) -> io::Result<Vec<String>> {
+    let file = File::open(path)?;
+    BufReader::new(file)
+        .lines()
+        .filter(|line| line.as_ref().map_or(true, |l| !l.trim().is_empty()))
+        .collect()
+}
+
+/// TradingView watchlist section header.
+#[must_use]
+pub fn section_header(name: &str) -> String {
+    format!("###{name}")
+}
+
+pub fn handle_file(data: &[String], name: &str, output: &Output) {
+    handle_entries(&Entry::from_lines(data), name, output);
+}
+
+/// Writes `entries` once per requested format, after comparing them with
+/// the previous version when a diff was requested.
+pub fn handle_entries(entries: &[Entry], name: &str, output: &Output) {
+    if output.show_diff || output.new_listings {
+        compare_previous(entries, name, output);
+    }
+
+    write_entries(entries, name, output);
+}
+
+/// Compares `entries` with the file of the first requested format, which
+/// every run rewrites. Without a previous file there is nothing to compare
+/// with, so nothing counts as added.
+fn compare_previous(entries: &[Entry], name: &str, output: &Output) {
+    let format = output.formats.first().copied().unwrap_or(Format::Tv);
+    let previous_path = output.path(name, format.extension());
+    if !previous_path.exists() {
+        info!(watchlist = name, "No previous version to compare with");
+        return;
+    }
+    let previous = match read_file(&previous_path) {
+        Ok(lines) => output::keys(&lines, format),
+        Err(e) => {
+            warn!(error = %e, path = %previous_path.display(), "Failed to read previous version");
+            return;
+        }
+    };
+    let current: Vec<String> = entries.iter().map(|e| e.key(format).to_string()).collect();
+    let changes = diff::compare(&previous, &current);
+
+    if output.show_diff {
+        changes.log(name);
+    }
+    if output.new_listings {
+        let added: HashSet<&str> = changes.added.iter().map(String::as_str).collect();
+        let new: Vec<Entry> = entries
+            .iter()
+            .filter(|e| added.contains(e.key(format)))
+            .cloned()
+            .collect();
+        write_entries(&new, &format!("{name} - New listings"), output);
+    }
+}
+
+fn write_entries(entries: &[Entry], name: &str, output: &Output) {
+    let mut changed = false;
+    for format in &output.formats {
+        let path = output.path(name, format.extension());
+        let lines = output::render(entries, *format);
+        if unchanged(&lines, &path) {
+            continue;
+        }
+        changed = true;
+        if let Err(e) = archive(
+            &path,
+            &lines,
+            &output.history_dir(name),
+            &output.timestamp,
+            format.extension(),
+            output.history,
+        ) {
+            warn!(error = %e, path = %path.display(), "Failed to archive");
+        }
+        if let Err(e) = write_file(&lines, &path) {
+            error!(error = %e, path = %path.display(), "Failed to write");
+            return;
+        }
+    }
+    if changed {
+        info!(watchlist = name, tickers = entries.len(), "Written");
+    } else {
+        info!(watchlist = name, tickers = entries.len(), "Unchanged");
+    }
 }
 
-pub fn handle_file(data: &[String], name: &str) {
-    match write_file(data, name) {
-        Ok(()) => eprintln!("{name}: {} tickers", data.len()),
-        Err(e) => eprintln!("Error: {e:#?}"),
+pub fn handle_report(rows: &[String], name: &str, output: &Output) {
+    let path = output.path(name, "csv");
+    if unchanged(rows, &path) {
+        info!(report = name, "Unchanged");
+        return;
+    }
+    match write_file(rows, &path) {
+        Ok(()) => info!(
+            report = name,
+            rows = rows.len().saturating_sub(1),
+            "Written"
+        ),
+        Err(e) => error!(error = %e, path = %path.display(), "Failed to write"),
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("watchlist-{}-{name}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn lines(symbols: &[&str]) -> Vec<String> {
+        symbols.iter().map(|s| (*s).to_string()).collect()
+    }
+
+    #[test]
+    fn test_write_file_creates_directories_and_leaves_no_temp_file() {
+        let dir = temp_dir("write");
+        let path = dir.join("nested").join("- C - TEST.txt");
+
+        write_file(&lines(&["BINANCE:BTCUSDT"]), &path).expect("Failed to write");
+
+        assert_eq!(
+            read_file(&path).expect("Failed to read"),
+            lines(&["BINANCE:BTCUSDT"])
+        );
+        assert!(!temp_path(&path).exists());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_unchanged_compares_whole_file() {
+        let dir = temp_dir("unchanged");
+        let path = dir.join("- C - TEST.txt");
+
+        assert!(!unchanged(&lines(&["A"]), &path));
+        write_file(&lines(&["A", "B"]), &path).expect("Failed to write");
+
+        assert!(unchanged(&lines(&["A", "B"]), &path));
+        assert!(!unchanged(&lines(&["A"]), &path));
+        assert!(!unchanged(&lines(&["A", "B", "C"]), &path));
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_new_listings_need_a_previous_version() {
+        let dir = temp_dir("new-listings");
+        let output = Output {
+            formats: vec![Format::Csv],
+            dir: dir.clone(),
+            template: "- {market} - {source}".to_string(),
+            date: chrono::NaiveDate::from_ymd_opt(2025, 10, 30).expect("Invalid date"),
+            history: 0,
+            timestamp: "20251030T120000".to_string(),
+            show_diff: false,
+            new_listings: true,
+        };
+        let new_listings = dir.join("- C - TEST - New listings.csv");
+
+        handle_file(&lines(&["BINANCE:BTCUSDT"]), "- C - TEST", &output);
+        assert!(!new_listings.exists());
+
+        handle_file(
+            &lines(&["BINANCE:BTCUSDT", "BINANCE:NEWUSDT"]),
+            "- C - TEST",
+            &output,
+        );
+        assert_eq!(
+            output::keys(
+                &read_file(&new_listings).expect("Failed to read"),
+                Format::Csv
+            ),
+            lines(&["BINANCE:NEWUSDT"])
+        );
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_archive_keeps_newest_changed_copies() {
+        let dir = temp_dir("archive");
+        let path = dir.join("- C - TEST.txt");
+        let history = dir.join("history").join("- C - TEST");
+
+        for (stamp, symbol) in [("1", "A"), ("2", "B"), ("3", "C"), ("4", "C")] {
+            let next = lines(&[symbol]);
+            archive(&path, &next, &history, stamp, "txt", 2).expect("Failed to archive");
+            write_file(&next, &path).expect("Failed to write");
+        }
+        // Another format sharing the history directory is left alone.
+        fs::write(history.join("0.csv"), "ticker\n").expect("Failed to write csv");
+        archive(&path, &lines(&["D"]), &history, "5", "txt", 2).expect("Failed to archive");
+
+        let mut kept: Vec<String> = fs::read_dir(&history)
+            .expect("Failed to list history")
+            .filter_map(|e| e.ok()?.file_name().into_string().ok())
+            .collect();
+        kept.sort();
+
+        assert_eq!(kept, vec!["0.csv", "3.txt", "5.txt"]);
+        assert_eq!(
+            read_file(&history.join("5.txt")).expect("Failed to read"),
+            lines(&["C"])
+        );
+        let _ = fs::remove_dir_all(&dir);
     }
 }